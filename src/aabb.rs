@@ -0,0 +1,99 @@
+use crate::ray::Ray;
+use crate::vec3::Point3;
+
+#[derive(Debug, Clone)]
+pub struct Aabb {
+    pub minimum: Point3,
+    pub maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(minimum: Point3, maximum: Point3) -> Self {
+        Aabb { minimum, maximum }
+    }
+
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.interval(r, t_min, t_max).is_some()
+    }
+
+    // Part of [t_min, t_max] that the ray spends inside the box
+    pub fn interval(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for axis in 0..3 {
+            let inv_d = 1.0 / r.direction()[axis];
+            let mut t0 = (self.minimum[axis] - r.origin()[axis]) * inv_d;
+            let mut t1 = (self.maximum[axis] - r.origin()[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t0.max(t_min);
+            t_max = t1.min(t_max);
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    // Widens axes thinner than delta, so that planar objects still get hit
+    pub fn pad(self, delta: f64) -> Aabb {
+        let mut minimum = self.minimum;
+        let mut maximum = self.maximum;
+        let widen = |min: &mut f64, max: &mut f64| {
+            if *max - *min < delta {
+                *min -= delta / 2.0;
+                *max += delta / 2.0;
+            }
+        };
+        widen(&mut minimum.x, &mut maximum.x);
+        widen(&mut minimum.y, &mut maximum.y);
+        widen(&mut minimum.z, &mut maximum.z);
+        Aabb::new(minimum, maximum)
+    }
+
+    pub fn centroid(&self, axis: usize) -> f64 {
+        0.5 * (self.minimum[axis] + self.maximum[axis])
+    }
+
+    // Axis along which the box is widest
+    pub fn longest_axis(&self) -> usize {
+        let extent = &self.maximum - &self.minimum;
+        if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn surrounding_box(box0: &Aabb, box1: &Aabb) -> Aabb {
+        let small = Point3::new(
+            box0.minimum.x.min(box1.minimum.x),
+            box0.minimum.y.min(box1.minimum.y),
+            box0.minimum.z.min(box1.minimum.z),
+        );
+        let big = Point3::new(
+            box0.maximum.x.max(box1.maximum.x),
+            box0.maximum.y.max(box1.maximum.y),
+            box0.maximum.z.max(box1.maximum.z),
+        );
+        Aabb::new(small, big)
+    }
+
+    // May be empty, with minimum above maximum on some axis
+    pub fn overlap(box0: &Aabb, box1: &Aabb) -> Aabb {
+        let small = Point3::new(
+            box0.minimum.x.max(box1.minimum.x),
+            box0.minimum.y.max(box1.minimum.y),
+            box0.minimum.z.max(box1.minimum.z),
+        );
+        let big = Point3::new(
+            box0.maximum.x.min(box1.maximum.x),
+            box0.maximum.y.min(box1.maximum.y),
+            box0.maximum.z.min(box1.maximum.z),
+        );
+        Aabb::new(small, big)
+    }
+}
@@ -0,0 +1,298 @@
+// Keyframe animation. Tracks hold values at key times and can be sampled at
+// any time in between, an Animation turns them into one Scene per frame.
+
+use crate::bvh::BvhNode;
+use crate::camera::Camera;
+use crate::hittable::Hittable;
+use crate::hittable_list::HittableList;
+use crate::instance::Instance;
+use crate::scene::{Background, Scene};
+use crate::transform::Transform;
+use crate::vec3::{Point3, Vec3};
+use std::sync::Arc;
+
+// Values that tracks can blend
+pub trait Animatable: Clone {
+    fn scaled(&self, s: f64) -> Self;
+    fn added(&self, other: &Self) -> Self;
+}
+
+impl Animatable for f64 {
+    fn scaled(&self, s: f64) -> Self {
+        self * s
+    }
+
+    fn added(&self, other: &Self) -> Self {
+        self + other
+    }
+}
+
+impl Animatable for Vec3 {
+    fn scaled(&self, s: f64) -> Self {
+        s * self
+    }
+
+    fn added(&self, other: &Self) -> Self {
+        self + other
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    // Catmull-Rom spline through the keys, smooth across them
+    Spline,
+}
+
+pub struct Track<T> {
+    keys: Vec<(f64, T)>,
+    interpolation: Interpolation,
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Track {
+            keys: Vec::new(),
+            interpolation,
+        }
+    }
+
+    pub fn constant(value: T) -> Self {
+        Track::new(Interpolation::Linear).key(0.0, value)
+    }
+
+    // Keys can be given in any order
+    pub fn key(mut self, time: f64, value: T) -> Self {
+        let index = self.keys.partition_point(|(t, _)| *t <= time);
+        self.keys.insert(index, (time, value));
+        self
+    }
+
+    // Holds the first and last values outside of the keys
+    pub fn sample(&self, time: f64) -> T {
+        assert!(!self.keys.is_empty(), "Track needs at least one key.");
+        let last = self.keys.len() - 1;
+        if time <= self.keys[0].0 {
+            return self.keys[0].1.clone();
+        }
+        if time >= self.keys[last].0 {
+            return self.keys[last].1.clone();
+        }
+
+        let i = self.keys.partition_point(|(t, _)| *t <= time) - 1;
+        let (t1, p1) = &self.keys[i];
+        let (t2, p2) = &self.keys[i + 1];
+        let u = (time - t1) / (t2 - t1);
+
+        match self.interpolation {
+            Interpolation::Linear => p1.scaled(1.0 - u).added(&p2.scaled(u)),
+            Interpolation::Spline => {
+                // The end keys are repeated to get tangents at the ends
+                let p0 = &self.keys[i.saturating_sub(1)].1;
+                let p3 = &self.keys[(i + 2).min(last)].1;
+                let (u2, u3) = (u * u, u * u * u);
+                p0.scaled(0.5 * (-u + 2.0 * u2 - u3))
+                    .added(&p1.scaled(0.5 * (2.0 - 5.0 * u2 + 3.0 * u3)))
+                    .added(&p2.scaled(0.5 * (u + 4.0 * u2 - 3.0 * u3)))
+                    .added(&p3.scaled(0.5 * (-u2 + u3)))
+            }
+        }
+    }
+}
+
+// Scale, then rotation in degrees around X, Y and Z, then position
+pub struct TransformTrack {
+    position: Track<Vec3>,
+    rotation: Track<Vec3>,
+    scale: Track<Vec3>,
+}
+
+impl TransformTrack {
+    pub fn new() -> Self {
+        TransformTrack {
+            position: Track::constant(Vec3::new(0.0, 0.0, 0.0)),
+            rotation: Track::constant(Vec3::new(0.0, 0.0, 0.0)),
+            scale: Track::constant(Vec3::new(1.0, 1.0, 1.0)),
+        }
+    }
+
+    pub fn position(mut self, track: Track<Vec3>) -> Self {
+        self.position = track;
+        self
+    }
+
+    pub fn rotation(mut self, track: Track<Vec3>) -> Self {
+        self.rotation = track;
+        self
+    }
+
+    pub fn scale(mut self, track: Track<Vec3>) -> Self {
+        self.scale = track;
+        self
+    }
+
+    pub fn at(&self, time: f64) -> Transform {
+        let rotation = self.rotation.sample(time);
+        Transform::identity()
+            .scale(self.scale.sample(time))
+            .rotate(Vec3::new(1.0, 0.0, 0.0), rotation.x)
+            .rotate(Vec3::new(0.0, 1.0, 0.0), rotation.y)
+            .rotate(Vec3::new(0.0, 0.0, 1.0), rotation.z)
+            .translate(self.position.sample(time))
+    }
+}
+
+pub struct CameraTrack {
+    lookfrom: Track<Point3>,
+    lookat: Track<Point3>,
+    vertical_fov: Track<f64>,
+    aperture: Track<f64>,
+    focus_dist: Track<f64>,
+}
+
+impl CameraTrack {
+    // A pinhole camera with a 20 degree field of view, focused at 10
+    pub fn new(lookfrom: Track<Point3>, lookat: Track<Point3>) -> Self {
+        CameraTrack {
+            lookfrom,
+            lookat,
+            vertical_fov: Track::constant(20.0),
+            aperture: Track::constant(0.0),
+            focus_dist: Track::constant(10.0),
+        }
+    }
+
+    pub fn vertical_fov(mut self, track: Track<f64>) -> Self {
+        self.vertical_fov = track;
+        self
+    }
+
+    pub fn aperture(mut self, track: Track<f64>) -> Self {
+        self.aperture = track;
+        self
+    }
+
+    pub fn focus_dist(mut self, track: Track<f64>) -> Self {
+        self.focus_dist = track;
+        self
+    }
+
+    pub fn at(&self, time: f64, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.lookfrom.sample(time),
+            self.lookat.sample(time),
+            Vec3::new(0.0, 1.0, 0.0),
+            self.vertical_fov.sample(time),
+            aspect_ratio,
+            self.aperture.sample(time),
+            self.focus_dist.sample(time),
+        )
+    }
+}
+
+pub struct Animation {
+    // Objects that never move, in a BVH built once and shared by every frame
+    static_world: Arc<BvhNode>,
+    animated: Vec<(Arc<dyn Hittable + Sync + Send + 'static>, TransformTrack)>,
+    // Static emitters of the world, sampled directly
+    lights: Vec<Arc<dyn Hittable + Sync + Send + 'static>>,
+    background: Background,
+    camera: CameraTrack,
+}
+
+impl Animation {
+    pub fn new(static_world: HittableList, background: Background, camera: CameraTrack) -> Self {
+        Animation {
+            static_world: Arc::new(BvhNode::new(static_world, 0.0, 0.0)),
+            animated: Vec::new(),
+            lights: Vec::new(),
+            background,
+            camera,
+        }
+    }
+
+    pub fn animate(
+        mut self,
+        object: Arc<dyn Hittable + Sync + Send + 'static>,
+        track: TransformTrack,
+    ) -> Self {
+        self.animated.push((object, track));
+        self
+    }
+
+    pub fn light(mut self, light: Arc<dyn Hittable + Sync + Send + 'static>) -> Self {
+        self.lights.push(light);
+        self
+    }
+
+    // Only the top level BVH over the static world and the animated
+    // instances is rebuilt
+    pub fn frame(&self, time: f64, aspect_ratio: f64) -> Scene {
+        let world = self.animated.iter().fold(
+            HittableList::new().push(self.static_world.clone()),
+            |world, (object, track)| world.push(Instance::new(object.clone(), track.at(time))),
+        );
+        let lights = self
+            .lights
+            .iter()
+            .map(|light| Box::new(light.clone()) as Box<dyn Hittable + Sync + Send + 'static>)
+            .collect();
+        Scene::new(
+            world,
+            lights,
+            self.background.clone(),
+            self.camera.at(time, aspect_ratio),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> [(f64, f64); 4] {
+        [(0.0, 1.0), (1.0, 3.0), (2.5, -2.0), (4.0, 0.5)]
+    }
+
+    #[test]
+    fn spline_passes_through_keys() {
+        // Given out of order
+        let track = keys()
+            .iter()
+            .rev()
+            .fold(Track::new(Interpolation::Spline), |track, &(t, value)| {
+                track.key(t, value)
+            });
+        for (t, value) in keys().iter() {
+            assert!((track.sample(*t) - value).abs() < 1e-12);
+        }
+        // Held outside of the keys
+        assert_eq!(track.sample(-1.0), 1.0);
+        assert_eq!(track.sample(5.0), 0.5);
+    }
+
+    #[test]
+    fn spline_is_smooth_across_evenly_spaced_keys() {
+        let track = [1.0, 3.0, -2.0, 0.5]
+            .iter()
+            .enumerate()
+            .fold(Track::new(Interpolation::Spline), |track, (i, &value)| {
+                track.key(i as f64, value)
+            });
+        let h = 1e-6;
+        for t in [1.0, 2.0].iter() {
+            let before = (track.sample(*t) - track.sample(t - h)) / h;
+            let after = (track.sample(t + h) - track.sample(*t)) / h;
+            assert!((before - after).abs() < 1e-4, "{} != {}", before, after);
+        }
+    }
+
+    #[test]
+    fn linear_blends_neighbouring_keys() {
+        let track = Track::new(Interpolation::Linear)
+            .key(0.0, Vec3::new(0.0, 0.0, 0.0))
+            .key(2.0, Vec3::new(2.0, 4.0, -2.0));
+        let mid = track.sample(0.5);
+        assert!((&mid - &Vec3::new(0.5, 1.0, -0.5)).length() < 1e-12);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::hittable::Hittable;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vec3::Color;
+use std::str::FromStr;
+
+// Arbitrary output variables: debug views computed from the primary hit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aov {
+    Beauty,
+    Normal,
+    Distance,
+    Depth,
+    FrontFace,
+    MaterialId,
+    ObjectId,
+    Uv,
+    TraversalCost,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 9] = [
+        Aov::Beauty,
+        Aov::Normal,
+        Aov::Distance,
+        Aov::Depth,
+        Aov::FrontFace,
+        Aov::MaterialId,
+        Aov::ObjectId,
+        Aov::Uv,
+        Aov::TraversalCost,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Beauty => "beauty",
+            Aov::Normal => "normal",
+            Aov::Distance => "distance",
+            Aov::Depth => "depth",
+            Aov::FrontFace => "front_face",
+            Aov::MaterialId => "material_id",
+            Aov::ObjectId => "object_id",
+            Aov::Uv => "uv",
+            Aov::TraversalCost => "cost",
+        }
+    }
+
+    // Only the beauty pass holds radiance, everything else is written as is
+    pub fn gamma_correct(&self) -> bool {
+        *self == Aov::Beauty
+    }
+
+    // Value of one camera sample. Distance, depth and cost are raw here and
+    // get mapped to displayable colors by `finish` once the whole image is known.
+    pub fn sample(&self, r: &Ray, scene: &Scene) -> Color {
+        if *self == Aov::TraversalCost {
+            let cost = scene.world.traversal_cost(r, 0.001, f64::INFINITY) as f64;
+            return Color::new(cost, cost, cost);
+        }
+
+        let rec = match scene.hit(r, 0.001, f64::INFINITY) {
+            Some(rec) => rec,
+            None => return Color::new(0., 0., 0.),
+        };
+        match self {
+            Aov::Beauty | Aov::TraversalCost => unreachable!(),
+            Aov::Normal => 0.5 * (rec.normal + Color::new(1., 1., 1.)),
+            Aov::Distance => {
+                let d = rec.t * r.direction().length();
+                Color::new(d, d, d)
+            }
+            Aov::Depth => {
+                let d = scene.camera.view_depth(&rec.p);
+                Color::new(d, d, d)
+            }
+            Aov::FrontFace => {
+                if rec.front_face {
+                    Color::new(0., 1., 0.)
+                } else {
+                    Color::new(1., 0., 0.)
+                }
+            }
+            Aov::MaterialId => rec
+                .material_id
+                .map_or(Color::new(1., 1., 1.), |id| id_to_color(id + 1)),
+            Aov::ObjectId => rec
+                .object_id
+                .map_or(Color::new(1., 1., 1.), |id| id_to_color(id + 1)),
+            Aov::Uv => Color::new(rec.u, rec.v, 0.),
+        }
+    }
+
+    pub fn finish(&self, pixel_colors: &mut [Color]) {
+        match self {
+            Aov::Distance | Aov::Depth => {
+                // Near is bright, far is dark, misses stay black
+                let max = pixel_colors.iter().fold(0.0_f64, |max, c| max.max(c.x));
+                if max > 0. {
+                    pixel_colors.iter_mut().for_each(|c| {
+                        if c.x > 0. {
+                            let d = 1. - c.x / max;
+                            *c = Color::new(d, d, d);
+                        }
+                    });
+                }
+            }
+            Aov::TraversalCost => {
+                let max = pixel_colors.iter().fold(0.0_f64, |max, c| max.max(c.x));
+                if max > 0. {
+                    pixel_colors
+                        .iter_mut()
+                        .for_each(|c| *c = heatmap(c.x / max));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl FromStr for Aov {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Aov::ALL
+            .iter()
+            .find(|aov| aov.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Aov::ALL.iter().map(|aov| aov.name()).collect();
+                format!("unknown AOV '{}', expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+// Spread ids over the hue circle by golden ratio steps, so that neighbouring
+// ids stay distinguishable
+fn id_to_color(id: usize) -> Color {
+    let hue = (id as f64 * 0.618_033_988_75).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    match hue as u64 {
+        0 => Color::new(1., x, 0.),
+        1 => Color::new(x, 1., 0.),
+        2 => Color::new(0., 1., x),
+        3 => Color::new(0., x, 1.),
+        4 => Color::new(x, 0., 1.),
+        _ => Color::new(1., 0., x),
+    }
+}
+
+// Blue (cheap) -> green -> red (expensive)
+fn heatmap(t: f64) -> Color {
+    let t = t.clamp(0., 1.);
+    if t < 0.5 {
+        Color::new(0., 2. * t, 1. - 2. * t)
+    } else {
+        Color::new(2. * t - 1., 2. - 2. * t, 0.)
+    }
+}
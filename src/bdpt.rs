@@ -0,0 +1,475 @@
+// Bidirectional path tracing (Veach 1997, following the structure of pbrt-v3).
+//
+// A camera subpath and a light subpath are traced independently, then every
+// prefix pair (s light vertices, t camera vertices) is connected. Each
+// strategy is weighted with the balance heuristic over all strategies that
+// could have produced the same path. Strategies with t == 1 connect a light
+// subpath vertex to the lens and land on an arbitrary pixel, so they are
+// splatted onto the film instead of being returned.
+
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vec3::{Color, Point3, Vec3};
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, PartialEq)]
+enum VertexKind {
+    Camera,
+    Light,
+    Surface,
+}
+
+#[derive(Clone)]
+struct Vertex {
+    kind: VertexKind,
+    p: Point3,
+    // Surface normal facing the previous vertex; the viewing direction for the camera
+    n: Vec3,
+    // Unit vector towards the previous vertex of the subpath
+    wo: Vec3,
+    rec: Option<HitRecord>,
+    beta: Color,
+    delta: bool,
+    // Area densities of sampling this vertex from its neighbours, in the
+    // direction the subpath was traced and in the opposite one
+    pdf_fwd: f64,
+    pdf_rev: f64,
+}
+
+impl Vertex {
+    fn camera(p: Point3, n: Vec3, beta: Color) -> Self {
+        Vertex {
+            kind: VertexKind::Camera,
+            p,
+            wo: n.clone(),
+            n,
+            rec: None,
+            beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn light(rec: HitRecord, beta: Color, pdf_fwd: f64) -> Self {
+        Vertex {
+            kind: VertexKind::Light,
+            p: rec.p.clone(),
+            n: rec.normal.clone(),
+            wo: rec.normal.clone(),
+            rec: Some(rec),
+            beta,
+            delta: false,
+            pdf_fwd,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn surface(rec: HitRecord, wo: Vec3, beta: Color, pdf_fwd: f64) -> Self {
+        Vertex {
+            kind: VertexKind::Surface,
+            p: rec.p.clone(),
+            n: rec.normal.clone(),
+            wo,
+            rec: Some(rec),
+            beta,
+            delta: false,
+            pdf_fwd,
+            pdf_rev: 0.0,
+        }
+    }
+
+    // BSDF value for the path continuing towards next
+    fn f(&self, next: &Vertex) -> Color {
+        match (&self.rec, self.kind) {
+            (Some(rec), VertexKind::Surface) => {
+                let wi = (&next.p - &self.p).normalize();
+                rec.mat_ptr.eval(&self.wo, &wi, rec)
+            }
+            _ => Color::new(0., 0., 0.),
+        }
+    }
+
+    // Cosine between the unit vector w and the surface, 1 inside media
+    fn abs_cos(&self, w: &Vec3) -> f64 {
+        match &self.rec {
+            Some(rec) if rec.mat_ptr.is_volumetric() => 1.0,
+            _ => self.n.dot(w).abs(),
+        }
+    }
+
+    // Radiance emitted from this vertex along w
+    fn le(&self, w: &Vec3) -> Color {
+        match &self.rec {
+            Some(rec) if self.n.dot(w) > 0. => rec.mat_ptr.emitted(rec),
+            _ => Color::new(0., 0., 0.),
+        }
+    }
+
+    // Area density of sampling next from this vertex, given that the path arrived from prev
+    fn pdf(&self, scene: &Scene, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        match self.kind {
+            VertexKind::Light => self.pdf_light(next),
+            VertexKind::Camera => scene
+                .camera
+                .importance(&self.p, &(&next.p - &self.p))
+                .map_or(0.0, |(_, pdf)| convert_density(pdf, self, next)),
+            VertexKind::Surface => {
+                let rec = self.rec.as_ref().unwrap();
+                if rec.mat_ptr.is_specular() {
+                    return 0.0;
+                }
+                let prev = prev.expect("surface vertex without predecessor");
+                let wp = (&prev.p - &self.p).normalize();
+                let wn = (&next.p - &self.p).normalize();
+                convert_density(rec.mat_ptr.pdf(&wp, &wn, rec), self, next)
+            }
+        }
+    }
+
+    // Area density of next when this vertex emits a light subpath
+    fn pdf_light(&self, next: &Vertex) -> f64 {
+        let w = (&next.p - &self.p).normalize();
+        let pdf_dir = self.n.dot(&w).max(0.) / PI;
+        convert_density(pdf_dir, self, next)
+    }
+
+    // Area density of this vertex being chosen as the start of a light subpath
+    fn pdf_light_origin(&self, scene: &Scene, prev: &Vertex) -> f64 {
+        scene.light_pdf(&prev.p, &self.p)
+    }
+}
+
+// Converts a solid angle density at `from` into an area density at `to`
+fn convert_density(pdf: f64, from: &Vertex, to: &Vertex) -> f64 {
+    let w = &to.p - &from.p;
+    let dist_squared = w.norm();
+    if dist_squared == 0. {
+        return 0.0;
+    }
+    let pdf = pdf / dist_squared;
+    if to.kind == VertexKind::Camera {
+        pdf
+    } else {
+        pdf * to.abs_cos(&(w / dist_squared.sqrt()))
+    }
+}
+
+fn is_black(c: &Color) -> bool {
+    c.x == 0. && c.y == 0. && c.z == 0.
+}
+
+fn visible(scene: &Scene, a: &Point3, b: &Point3, time: f64) -> bool {
+    let d = b - a;
+    let dist = d.length();
+    let r = Ray::new(a.clone(), d / dist, time);
+    scene.world.hit(&r, 0.001, dist - 0.001).is_none()
+}
+
+// Extends path by following scattered rays until it holds max_vertices vertices.
+// Returns the background radiance weighted by the throughput if the path escaped.
+fn random_walk(
+    scene: &Scene,
+    ray: Ray,
+    beta: Color,
+    pdf_dir: f64,
+    max_vertices: usize,
+    path: &mut Vec<Vertex>,
+) -> Color {
+    let mut ray = ray;
+    let mut beta = beta;
+    let mut pdf_fwd = pdf_dir;
+
+    while path.len() < max_vertices {
+        let rec = match scene.world.hit(&ray, 0.001, f64::INFINITY) {
+            Some(rec) => rec,
+            None => return &beta * &scene.background.color(&ray),
+        };
+
+        let wo = -ray.direction().clone().normalize();
+        let mut vertex = Vertex::surface(rec, wo.clone(), beta.clone(), 0.0);
+        vertex.pdf_fwd = convert_density(pdf_fwd, path.last().unwrap(), &vertex);
+        path.push(vertex);
+        if path.len() == max_vertices {
+            break;
+        }
+
+        let n = path.len();
+        let rec = path[n - 1].rec.as_ref().unwrap();
+        let (attenuation, scattered) = match rec.mat_ptr.scatter(&ray, rec) {
+            Some(v) => v,
+            None => break,
+        };
+        let wi = scattered.direction().clone().normalize();
+        let (pdf_dir_fwd, pdf_dir_rev) = if rec.mat_ptr.is_specular() {
+            path[n - 1].delta = true;
+            (0.0, 0.0)
+        } else {
+            (
+                rec.mat_ptr.pdf(&wo, &wi, rec),
+                rec.mat_ptr.pdf(&wi, &wo, rec),
+            )
+        };
+
+        beta = &beta * &attenuation;
+        path[n - 2].pdf_rev = convert_density(pdf_dir_rev, &path[n - 1], &path[n - 2]);
+        pdf_fwd = pdf_dir_fwd;
+        ray = scattered;
+    }
+
+    Color::new(0., 0., 0.)
+}
+
+fn camera_subpath(r: &Ray, scene: &Scene, max_vertices: usize) -> (Vec<Vertex>, Color) {
+    let pdf_dir = scene
+        .camera
+        .importance(r.origin(), r.direction())
+        .map_or(0.0, |(_, pdf)| pdf);
+    let mut path = vec![Vertex::camera(
+        r.origin().clone(),
+        scene.camera.forward(),
+        Color::new(1., 1., 1.),
+    )];
+    let escaped = random_walk(
+        scene,
+        r.clone(),
+        Color::new(1., 1., 1.),
+        pdf_dir,
+        max_vertices,
+        &mut path,
+    );
+    (path, escaped)
+}
+
+// The light subpath is traced at the same time as the camera subpath
+fn light_subpath(scene: &Scene, max_vertices: usize, time: f64) -> Vec<Vertex> {
+    let (rec, pdf_pos) = match scene.sample_light() {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+    let le = rec.mat_ptr.emitted(&rec);
+
+    // Cosine weighted direction around the outward normal
+    let mut direction = &rec.normal + Vec3::random_unit_vector(&mut rand::thread_rng());
+    if direction.near_zero() {
+        direction = rec.normal.clone();
+    }
+    let direction = direction.normalize();
+    let cos_theta = direction.dot(&rec.normal);
+    let pdf_dir = cos_theta / PI;
+    if is_black(&le) || pdf_dir <= 0. {
+        return Vec::new();
+    }
+
+    let ray = Ray::new(rec.p.clone(), direction, time);
+    let beta = (cos_theta / (pdf_pos * pdf_dir)) * &le;
+    let mut path = vec![Vertex::light(rec, le / pdf_pos, pdf_pos)];
+    random_walk(scene, ray, beta, pdf_dir, max_vertices, &mut path);
+    path
+}
+
+// Contribution of the strategy using s light and t camera vertices, and the
+// image position to splat it at when it does not belong to the current pixel
+fn connect(
+    scene: &Scene,
+    light_path: &[Vertex],
+    camera_path: &[Vertex],
+    s: usize,
+    t: usize,
+    time: f64,
+) -> Option<(Color, Option<(f64, f64)>)> {
+    let mut sampled = None;
+    let mut raster = None;
+
+    let l = if s == 0 {
+        // The camera subpath hit a light by itself
+        let pt = &camera_path[t - 1];
+        &pt.le(&(&camera_path[t - 2].p - &pt.p)) * &pt.beta
+    } else if t == 1 {
+        // Connect the light subpath to the lens
+        let qs = &light_path[s - 1];
+        if qs.delta {
+            return None;
+        }
+        let lens = scene.camera.sample_lens(&qs.p)?;
+        let wi = (&lens.p - &qs.p).normalize();
+        let importance = lens.importance / lens.pdf;
+        let camera = Vertex::camera(
+            lens.p,
+            scene.camera.forward(),
+            Color::new(importance, importance, importance),
+        );
+        let l = qs.abs_cos(&wi) * &(&(&qs.beta * &qs.f(&camera)) * &camera.beta);
+        if is_black(&l) || !visible(scene, &qs.p, &camera.p, time) {
+            return None;
+        }
+        raster = Some((lens.u, lens.v));
+        sampled = Some(camera);
+        l
+    } else if s == 1 {
+        // Next event estimation with a freshly sampled light point
+        let pt = &camera_path[t - 1];
+        if pt.delta {
+            return None;
+        }
+        let (rec, pdf_area) = scene.sample_light()?;
+        let d = &rec.p - &pt.p;
+        let wi = d.clone().normalize();
+        let cos_light = -rec.normal.dot(&wi);
+        if cos_light <= 0. {
+            return None;
+        }
+        let le = rec.mat_ptr.emitted(&rec);
+        let pdf = pdf_area * d.norm() / cos_light;
+        let light = Vertex::light(rec, le / pdf, pdf_area);
+        let l = pt.abs_cos(&wi) * &(&(&pt.beta * &pt.f(&light)) * &light.beta);
+        if is_black(&l) || !visible(scene, &pt.p, &light.p, time) {
+            return None;
+        }
+        sampled = Some(light);
+        l
+    } else {
+        // Connect two surface vertices
+        let qs = &light_path[s - 1];
+        let pt = &camera_path[t - 1];
+        if qs.delta || pt.delta {
+            return None;
+        }
+        let d = &pt.p - &qs.p;
+        let w = d.clone().normalize();
+        let g = qs.abs_cos(&w) * pt.abs_cos(&w) / d.norm();
+        let l = g * &(&(&qs.beta * &qs.f(pt)) * &(&pt.f(qs) * &pt.beta));
+        if is_black(&l) || !visible(scene, &qs.p, &pt.p, time) {
+            return None;
+        }
+        l
+    };
+
+    if is_black(&l) {
+        return None;
+    }
+    let weight = mis_weight(scene, light_path, camera_path, sampled.as_ref(), s, t);
+    Some((weight * &l, raster))
+}
+
+// Balance heuristic weight of strategy (s, t), computed from the ratios of the
+// densities with which neighbouring strategies would have produced the path
+fn mis_weight(
+    scene: &Scene,
+    light_path: &[Vertex],
+    camera_path: &[Vertex],
+    sampled: Option<&Vertex>,
+    s: usize,
+    t: usize,
+) -> f64 {
+    if s + t == 2 {
+        return 1.0;
+    }
+
+    // (pdf_fwd, pdf_rev, delta) of the vertices of this path, updated for the connection
+    let mut light: Vec<(f64, f64, bool)> = light_path[..s]
+        .iter()
+        .map(|v| (v.pdf_fwd, v.pdf_rev, v.delta))
+        .collect();
+    let mut camera: Vec<(f64, f64, bool)> = camera_path[..t]
+        .iter()
+        .map(|v| (v.pdf_fwd, v.pdf_rev, v.delta))
+        .collect();
+
+    let qs = match s {
+        0 => None,
+        1 => sampled,
+        _ => Some(&light_path[s - 1]),
+    };
+    let pt = if t == 1 {
+        sampled.unwrap()
+    } else {
+        &camera_path[t - 1]
+    };
+    let qs_minus = if s > 1 {
+        Some(&light_path[s - 2])
+    } else {
+        None
+    };
+    let pt_minus = if t > 1 {
+        Some(&camera_path[t - 2])
+    } else {
+        None
+    };
+
+    if let (1, Some(qs)) = (s, qs) {
+        light[0].0 = qs.pdf_fwd;
+    }
+
+    // The connection vertices are never degenerate
+    camera[t - 1].2 = false;
+    if s > 0 {
+        light[s - 1].2 = false;
+    }
+
+    camera[t - 1].1 = match qs {
+        Some(qs) => qs.pdf(scene, qs_minus, pt),
+        None => pt.pdf_light_origin(scene, pt_minus.unwrap()),
+    };
+    if let Some(pt_minus) = pt_minus {
+        camera[t - 2].1 = match qs {
+            Some(qs) => pt.pdf(scene, Some(qs), pt_minus),
+            None => pt.pdf_light(pt_minus),
+        };
+    }
+    if let Some(qs) = qs {
+        light[s - 1].1 = pt.pdf(scene, pt_minus, qs);
+        if let Some(qs_minus) = qs_minus {
+            light[s - 2].1 = qs.pdf(scene, Some(pt), qs_minus);
+        }
+    }
+
+    let remap = |pdf: f64| if pdf != 0. { pdf } else { 1. };
+
+    let mut sum_ri = 0.0;
+    let mut ri = 1.0;
+    for i in (1..t).rev() {
+        ri *= remap(camera[i].1) / remap(camera[i].0);
+        if !camera[i].2 && !camera[i - 1].2 {
+            sum_ri += ri;
+        }
+    }
+
+    let mut ri = 1.0;
+    for i in (0..s).rev() {
+        ri *= remap(light[i].1) / remap(light[i].0);
+        // Area lights are never delta distributions
+        let delta_light_vertex = i > 0 && light[i - 1].2;
+        if !light[i].2 && !delta_light_vertex {
+            sum_ri += ri;
+        }
+    }
+
+    1.0 / (1.0 + sum_ri)
+}
+
+// Radiance arriving along r. Light tracing contributions are splatted onto film.
+pub fn bdpt_color(r: &Ray, scene: &Scene, film: &Film, max_depth: usize) -> Color {
+    let (camera_path, escaped) = camera_subpath(r, scene, max_depth + 2);
+    let light_path = light_subpath(scene, max_depth + 1, r.time());
+
+    let mut l = escaped;
+    for t in 1..=camera_path.len() {
+        for s in 0..=light_path.len() {
+            if (s == 1 && t == 1) || s + t < 2 || s + t - 2 > max_depth {
+                continue;
+            }
+            if let Some((contribution, raster)) =
+                connect(scene, &light_path, &camera_path, s, t, r.time())
+            {
+                match raster {
+                    Some((u, v)) => film.add_splat(u, v, &contribution),
+                    None => l += &contribution,
+                }
+            }
+        }
+    }
+    l
+}
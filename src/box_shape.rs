@@ -0,0 +1,121 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::quad::Quad;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use rand::Rng;
+use std::sync::Arc;
+
+// Axis-aligned box made of six outward facing quads
+pub struct BoxShape {
+    sides: Vec<Quad>,
+    bbox: Aabb,
+}
+
+impl BoxShape {
+    pub fn new(
+        a: Point3,
+        b: Point3,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> BoxShape {
+        let min = Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let max = Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+
+        let dx = Vec3::new(max.x - min.x, 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y - min.y, 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z - min.z);
+
+        let sides = vec![
+            // front
+            Quad::new(
+                Point3::new(min.x, min.y, max.z),
+                dx.clone(),
+                dy.clone(),
+                mat_ptr.clone(),
+            ),
+            // right
+            Quad::new(
+                Point3::new(max.x, min.y, max.z),
+                -dz.clone(),
+                dy.clone(),
+                mat_ptr.clone(),
+            ),
+            // back
+            Quad::new(
+                Point3::new(max.x, min.y, min.z),
+                -dx.clone(),
+                dy.clone(),
+                mat_ptr.clone(),
+            ),
+            // left
+            Quad::new(min.clone(), dz.clone(), dy, mat_ptr.clone()),
+            // top
+            Quad::new(
+                Point3::new(min.x, max.y, max.z),
+                dx.clone(),
+                -dz.clone(),
+                mat_ptr.clone(),
+            ),
+            // bottom
+            Quad::new(min.clone(), dx, dz, mat_ptr),
+        ];
+
+        BoxShape {
+            sides,
+            bbox: Aabb::new(min, max),
+        }
+    }
+}
+
+impl Hittable for BoxShape {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (record, _) = self
+            .sides
+            .iter()
+            .fold((None, t_max), |(rec, closest_so_far), side| {
+                match side.hit(r, t_min, closest_so_far) {
+                    Some(v) => {
+                        let t = v.t;
+                        (Some(v), t)
+                    }
+                    None => (rec, closest_so_far),
+                }
+            });
+        record
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(self.bbox.clone().pad(0.0001))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        self.sides
+            .iter()
+            .for_each(|side| side.number_materials(ids));
+    }
+
+    fn traversal_cost(&self, _r: &Ray, _t_min: f64, _t_max: f64) -> usize {
+        self.sides.len()
+    }
+
+    // Picks a side proportionally to its area
+    fn sample_surface(&self) -> Option<HitRecord> {
+        let mut x = rand::thread_rng().gen_range(0.0..self.area());
+        for side in &self.sides {
+            if x < side.area() {
+                return side.sample_surface();
+            }
+            x -= side.area();
+        }
+        self.sides.last()?.sample_surface()
+    }
+
+    fn area(&self) -> f64 {
+        self.sides.iter().map(|side| side.area()).sum()
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.sides.capacity() * std::mem::size_of::<Quad>()
+    }
+}
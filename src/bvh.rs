@@ -0,0 +1,277 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::hittable_list::HittableList;
+use crate::material::MaterialIds;
+use crate::Ray;
+
+pub enum BvhNode {
+    Leaf {
+        object_id: usize,
+        object: Box<dyn Hittable + Sync + Send + 'static>,
+        bbox: Aabb,
+    },
+    Branch {
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+        bbox: Aabb,
+    },
+    // Root of a world with objects that have no bounding box, such as infinite
+    // planes. They are kept out of the tree and tested against every ray.
+    Unbounded {
+        objects: Vec<(usize, Box<dyn Hittable + Sync + Send + 'static>)>,
+        bounded: Option<Box<BvhNode>>,
+    },
+}
+
+impl BvhNode {
+    // Objects are bounded over the interval [time0, time1], usually the camera shutter
+    pub fn new(list: HittableList, time0: f64, time1: f64) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = list
+            .into_objects()
+            .into_iter()
+            .enumerate()
+            .map(|(object_id, object)| (object_id, object.bounding_box(time0, time1), object))
+            .partition(|(_, bbox, _)| bbox.is_some());
+
+        let leaves: Vec<BvhNode> = bounded
+            .into_iter()
+            .filter_map(|(object_id, bbox, object)| {
+                Some(BvhNode::Leaf {
+                    object_id,
+                    object,
+                    bbox: bbox?,
+                })
+            })
+            .collect();
+        if unbounded.is_empty() {
+            return Self::build(leaves);
+        }
+
+        BvhNode::Unbounded {
+            objects: unbounded
+                .into_iter()
+                .map(|(object_id, _, object)| (object_id, object))
+                .collect(),
+            bounded: if leaves.is_empty() {
+                None
+            } else {
+                Some(Box::new(Self::build(leaves)))
+            },
+        }
+    }
+
+    fn build(mut nodes: Vec<BvhNode>) -> Self {
+        assert!(!nodes.is_empty(), "BvhNode needs at least one object.");
+        if nodes.len() == 1 {
+            return nodes.pop().unwrap();
+        }
+
+        // Split at the median centroid along the longest axis of the node
+        let bbox = nodes
+            .iter()
+            .skip(1)
+            .fold(nodes[0].bbox().clone(), |acc, node| {
+                Aabb::surrounding_box(&acc, node.bbox())
+            });
+        let axis = bbox.longest_axis();
+        nodes.sort_by(|a, b| a.bbox().centroid(axis).total_cmp(&b.bbox().centroid(axis)));
+
+        let right = nodes.split_off(nodes.len() / 2);
+        BvhNode::Branch {
+            left: Box::new(Self::build(nodes)),
+            right: Box::new(Self::build(right)),
+            bbox,
+        }
+    }
+
+    fn bbox(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { bbox, .. } => bbox,
+            BvhNode::Branch { bbox, .. } => bbox,
+            BvhNode::Unbounded { .. } => unreachable!("Unbounded objects are not in the tree."),
+        }
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        match self {
+            BvhNode::Leaf {
+                object_id, object, ..
+            } => object.hit(r, t_min, t_max).map(|mut rec| {
+                rec.object_id.get_or_insert(*object_id);
+                rec
+            }),
+            BvhNode::Branch { left, right, bbox } => {
+                if !bbox.hit(r, t_min, t_max) {
+                    return None;
+                }
+                let hit_left = left.hit(r, t_min, t_max);
+                let closest_so_far = hit_left.as_ref().map_or(t_max, |rec| rec.t);
+                right.hit(r, t_min, closest_so_far).or(hit_left)
+            }
+            BvhNode::Unbounded { objects, bounded } => {
+                let (record, closest_so_far) = objects.iter().fold(
+                    (None, t_max),
+                    |(rec, closest_so_far), (object_id, object)| match object.hit(
+                        r,
+                        t_min,
+                        closest_so_far,
+                    ) {
+                        Some(mut v) => {
+                            v.object_id.get_or_insert(*object_id);
+                            let t = v.t;
+                            (Some(v), t)
+                        }
+                        None => (rec, closest_so_far),
+                    },
+                );
+                match bounded {
+                    Some(tree) => tree.hit(r, t_min, closest_so_far).or(record),
+                    None => record,
+                }
+            }
+        }
+    }
+
+    // Built for the interval given to new
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        match self {
+            BvhNode::Unbounded { .. } => None,
+            node => Some(node.bbox().clone()),
+        }
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        match self {
+            BvhNode::Leaf { object, .. } => object.number_materials(ids),
+            BvhNode::Unbounded { objects, bounded } => {
+                objects
+                    .iter()
+                    .for_each(|(_, object)| object.number_materials(ids));
+                if let Some(tree) = bounded {
+                    tree.number_materials(ids);
+                }
+            }
+            BvhNode::Branch { left, right, .. } => {
+                left.number_materials(ids);
+                right.number_materials(ids);
+            }
+        }
+    }
+
+    fn traversal_cost(&self, r: &Ray, t_min: f64, t_max: f64) -> usize {
+        match self {
+            BvhNode::Leaf { object, .. } => object.traversal_cost(r, t_min, t_max),
+            BvhNode::Branch { left, right, bbox } => {
+                if !bbox.hit(r, t_min, t_max) {
+                    return 1;
+                }
+                let closest_so_far = left.hit(r, t_min, t_max).map_or(t_max, |rec| rec.t);
+                1 + left.traversal_cost(r, t_min, t_max)
+                    + right.traversal_cost(r, t_min, closest_so_far)
+            }
+            BvhNode::Unbounded { objects, bounded } => {
+                objects
+                    .iter()
+                    .map(|(_, object)| object.traversal_cost(r, t_min, t_max))
+                    .sum::<usize>()
+                    + bounded
+                        .as_ref()
+                        .map_or(0, |tree| tree.traversal_cost(r, t_min, t_max))
+            }
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + match self {
+                BvhNode::Leaf { object, .. } => object.memory_size(),
+                BvhNode::Branch { left, right, .. } => left.memory_size() + right.memory_size(),
+                BvhNode::Unbounded { objects, bounded } => {
+                    objects.capacity()
+                        * std::mem::size_of::<(usize, Box<dyn Hittable + Sync + Send>)>()
+                        + objects
+                            .iter()
+                            .map(|(_, object)| object.memory_size())
+                            .sum::<usize>()
+                        + bounded.as_ref().map_or(0, |tree| tree.memory_size())
+                }
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lambertian::Lambertian;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::vec3::{Color, Point3, Vec3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::sync::Arc;
+
+    fn spheres(rng: &mut StdRng) -> Vec<(Point3, f64)> {
+        (0..200)
+            .map(|_| {
+                let center = Point3::new(
+                    rng.gen_range(-10.0..10.0),
+                    rng.gen_range(-10.0..10.0),
+                    rng.gen_range(-10.0..10.0),
+                );
+                (center, rng.gen_range(0.1..1.5))
+            })
+            .collect()
+    }
+
+    fn list(spheres: &[(Point3, f64)]) -> HittableList {
+        let material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+            Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        spheres
+            .iter()
+            .fold(HittableList::new(), |list, (center, radius)| {
+                list.push(Sphere::new(center.clone(), *radius, material.clone()))
+            })
+    }
+
+    #[test]
+    fn same_hits_as_a_list() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let spheres = spheres(&mut rng);
+        let brute_force = list(&spheres);
+        let bvh = BvhNode::new(list(&spheres), 0.0, 0.0);
+
+        for _ in 0..2000 {
+            let origin = Point3::new(
+                rng.gen_range(-15.0..15.0),
+                rng.gen_range(-15.0..15.0),
+                rng.gen_range(-15.0..15.0),
+            );
+            let r = Ray::new(origin, Vec3::random_unit_vector(&mut rng), 0.0);
+            match (
+                brute_force.hit(&r, 0.001, f64::INFINITY),
+                bvh.hit(&r, 0.001, f64::INFINITY),
+            ) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    assert!((a.t - b.t).abs() < 1e-12, "{} != {}", a.t, b.t);
+                    assert!((&a.normal - &b.normal).length() < 1e-12);
+                }
+                (a, b) => panic!("list hit {}, bvh hit {}", a.is_some(), b.is_some()),
+            }
+        }
+    }
+
+    #[test]
+    fn nan_centroids_do_not_panic() {
+        let spheres = vec![
+            (Point3::new(0.0, 0.0, 0.0), 1.0),
+            (Point3::new(f64::NAN, 0.0, 0.0), 1.0),
+            (Point3::new(3.0, 0.0, 0.0), 1.0),
+        ];
+        let bvh = BvhNode::new(list(&spheres), 0.0, 0.0);
+        let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(bvh.hit(&r, 0.001, f64::INFINITY).is_some());
+    }
+}
@@ -1,5 +1,18 @@
 use crate::ray::Ray;
 use crate::vec3::*;
+use rand::Rng;
+use std::f64::consts::PI;
+
+// A point on the lens that sees a given scene point
+pub struct LensSample {
+    pub p: Point3,
+    // Image coordinates, same convention as get_ray
+    pub u: f64,
+    pub v: f64,
+    pub importance: f64,
+    // Solid angle density, as seen from the scene point
+    pub pdf: f64,
+}
 
 pub struct Camera {
     origin: Point3,
@@ -10,6 +23,10 @@ pub struct Camera {
     v: Vec3,
     w: Vec3,
     lens_radius: f64,
+    focus_dist: f64,
+    // Shutter open and close times
+    time0: f64,
+    time1: f64,
 }
 
 impl Camera {
@@ -47,9 +64,100 @@ impl Camera {
             v,
             w,
             lens_radius,
+            focus_dist,
+            time0: 0.0,
+            time1: 0.0,
+        }
+    }
+
+    pub fn with_shutter(mut self, time0: f64, time1: f64) -> Self {
+        self.time0 = time0;
+        self.time1 = time1;
+        self
+    }
+
+    pub fn shutter(&self) -> (f64, f64) {
+        (self.time0, self.time1)
+    }
+
+    // Uniform time while the shutter is open
+    pub fn sample_time(&self) -> f64 {
+        if self.time1 > self.time0 {
+            rand::thread_rng().gen_range(self.time0..self.time1)
+        } else {
+            self.time0
         }
     }
 
+    // Distance of p from the camera along the viewing direction
+    pub fn view_depth(&self, p: &Point3) -> f64 {
+        -(p - &self.origin).dot(&self.w)
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        -self.w.clone()
+    }
+
+    // Area of the image rectangle at unit distance from the lens
+    fn film_area(&self) -> f64 {
+        self.horizontal.length() * self.vertical.length() / self.focus_dist.powi(2)
+    }
+
+    fn lens_area(&self) -> f64 {
+        if self.lens_radius > 0. {
+            PI * self.lens_radius.powi(2)
+        } else {
+            1.0
+        }
+    }
+
+    // Image coordinates of the ray leaving the lens at origin along direction
+    pub fn raster(&self, origin: &Point3, direction: &Vec3) -> Option<(f64, f64)> {
+        let cos_theta = direction.dot(&self.forward());
+        if cos_theta <= 0. {
+            return None;
+        }
+        // Every ray through the same point of the focus plane lands on the same pixel
+        let focus = origin + (self.focus_dist / cos_theta) * direction;
+        let d = focus - &self.lower_left_corner;
+        let u = d.dot(&self.horizontal) / self.horizontal.norm();
+        let v = d.dot(&self.vertical) / self.vertical.norm();
+        if (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v) {
+            Some((u, v))
+        } else {
+            None
+        }
+    }
+
+    // Importance emitted along a ray leaving the lens, and the solid angle
+    // density with which get_ray generates its direction
+    pub fn importance(&self, origin: &Point3, direction: &Vec3) -> Option<(f64, f64)> {
+        self.raster(origin, direction)?;
+        let cos_theta = direction.clone().normalize().dot(&self.forward());
+        let importance = 1.0 / (self.film_area() * self.lens_area() * cos_theta.powi(4));
+        let pdf = 1.0 / (self.film_area() * cos_theta.powi(3));
+        Some((importance, pdf))
+    }
+
+    pub fn sample_lens(&self, p: &Point3) -> Option<LensSample> {
+        let mut rng = rand::thread_rng();
+        let rd = self.lens_radius * Vec3::random_in_unit_disk(&mut rng);
+        let lens_point = &self.origin + rd.x * &self.u + rd.y * &self.v;
+
+        let direction = p - &lens_point;
+        let (u, v) = self.raster(&lens_point, &direction)?;
+        let (importance, _) = self.importance(&lens_point, &direction)?;
+        let cos_theta = direction.clone().normalize().dot(&self.forward());
+        let pdf = direction.norm() / (cos_theta * self.lens_area());
+        Some(LensSample {
+            p: lens_point,
+            u,
+            v,
+            importance,
+            pdf,
+        })
+    }
+
     pub fn get_ray(&self, u: f64, v: f64) -> Ray {
         let mut rng = rand::thread_rng();
         let rd = self.lens_radius * Vec3::random_in_unit_disk(&mut rng);
@@ -60,6 +168,7 @@ impl Camera {
             &(self.lower_left_corner) + u * &(self.horizontal) + v * &(self.vertical)
                 - &(self.origin)
                 - offset,
+            self.sample_time(),
         )
     }
 }
@@ -0,0 +1,213 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::microfacet::{fresnel_dielectric, Ggx};
+use crate::onb::Onb;
+use crate::ray::{reflect, refract};
+use crate::vec3::Vec3;
+use crate::Color;
+use crate::Ray;
+use rand::Rng;
+use std::sync::Arc;
+
+// Reflections inside the coating before a path is given up on
+const MAX_BOUNCES: usize = 8;
+
+// A dielectric coating over any other material, like varnish or the clearcoat
+// of car paint. Each sample either reflects off the coating by its Fresnel
+// factor, or walks through it: refracting in, scattering off the base,
+// and reflecting back down at the interface until it refracts out, tinted by
+// absorption along the way. Evaluation and density are estimated by the same
+// kind of walk, connecting to the other direction at every scattering off the
+// base as in pbrt-v4's LayeredBxDF, so a coating over a non-specular base can
+// be lit directly.
+pub struct Coated {
+    base: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    index_of_refraction: f64,
+    distribution: Ggx,
+    // Absorption coefficient times thickness, the optical depth straight
+    // through the coating
+    optical_depth: Color,
+}
+
+impl Coated {
+    pub fn new(
+        base: Arc<Box<dyn Material + Sync + Send + 'static>>,
+        index_of_refraction: f64,
+    ) -> Self {
+        Self {
+            base,
+            index_of_refraction,
+            distribution: Ggx::new(0.0),
+            optical_depth: Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    // Roughness of the top of the coating, for its reflection
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.distribution = Ggx::new(roughness);
+        self
+    }
+
+    // Absorption per unit length inside the coating, and how thick it is
+    pub fn with_absorption(mut self, sigma_a: Color, thickness: f64) -> Self {
+        self.optical_depth = thickness * &sigma_a;
+        self
+    }
+
+    // Of one crossing of the coating at the given angle to the normal
+    fn transmittance(&self, cos_theta: f64) -> Color {
+        let length = 1.0 / cos_theta.abs().max(1e-4);
+        Color::new(
+            (-self.optical_depth.x * length).exp(),
+            (-self.optical_depth.y * length).exp(),
+            (-self.optical_depth.z * length).exp(),
+        )
+    }
+
+    // Estimates of the part of eval and pdf that goes through the coating, by
+    // one random walk down from wo. At every scattering off the base the walk
+    // connects to wi, refracted into the coating when above the surface. The
+    // walk's own reflections at the interface are weighted by their Fresnel
+    // factor rather than chosen at random.
+    fn walk(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> (Color, f64) {
+        let ior = self.index_of_refraction;
+        let normal = &rec.normal;
+        let cos_i = wi.dot(normal);
+        // Direction inside that leaves along wi, with the factors of refracting
+        // out for the BSDF and for the density
+        let (exit, exit_weight, exit_jacobian) = if cos_i > 0. {
+            let inside = -refract(&-wi.clone(), normal, 1. / ior);
+            let cos_inside = inside.dot(normal);
+            let t = 1. - fresnel_dielectric(cos_i, ior);
+            let weight = &self.transmittance(cos_inside) * (t / (ior * ior));
+            (inside, weight, t * cos_i / (ior * ior * cos_inside))
+        } else {
+            (wi.clone(), Color::new(1., 1., 1.), 1.)
+        };
+
+        let mut direction = refract(&-wo.clone(), normal, 1. / ior);
+        let mut throughput = Color::new(1., 1., 1.);
+        let mut reached = 1.;
+        let mut f = Color::new(0., 0., 0.);
+        let mut pdf = 0.;
+        for _ in 0..MAX_BOUNCES {
+            throughput = &throughput * &self.transmittance(direction.dot(normal));
+            let toward = -direction.clone();
+            f += &(&(&throughput * &self.base.eval(&toward, &exit, rec)) * &exit_weight);
+            pdf += reached * self.base.pdf(&toward, &exit, rec) * exit_jacobian;
+
+            let inner = Ray::new(rec.p.clone(), direction, 0.);
+            let (base_attenuation, scattered) = match self.base.scatter(&inner, rec) {
+                Some(scattered) => scattered,
+                None => break,
+            };
+            let up = scattered.direction().clone().normalize();
+            let cos_up = up.dot(normal);
+            if cos_up <= 0. {
+                break;
+            }
+            let fresnel = fresnel_dielectric(cos_up, 1. / ior);
+            throughput =
+                &(&throughput * &base_attenuation) * &(fresnel * &self.transmittance(cos_up));
+            reached *= fresnel;
+            direction = reflect(&up, normal);
+        }
+        (f, pdf)
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let mut rng = rand::thread_rng();
+        let unit_direction = r_in.direction().clone().normalize();
+        let cos_theta = -unit_direction.dot(&rec.normal);
+        let fresnel = fresnel_dielectric(cos_theta, self.index_of_refraction);
+
+        if rng.gen::<f64>() < fresnel {
+            // Off the coating, with the microfacet's Fresnel factor over the
+            // chance of getting here
+            let frame = Onb::build_from_hit(rec);
+            let wo = frame.to_local(&-unit_direction);
+            let h = self.distribution.sample_visible(&wo, rng.gen(), rng.gen());
+            let wi = 2. * wo.dot(&h) * &h - wo.clone();
+            if wi.z <= 0. {
+                return None;
+            }
+            let weight = self.distribution.g(&wo, &wi) / self.distribution.g1(&wo)
+                * fresnel_dielectric(wo.dot(&h), self.index_of_refraction)
+                / fresnel;
+            let scattered = Ray::new(rec.p.clone(), frame.local(wi.x, wi.y, wi.z), r_in.time());
+            return Some((Color::new(weight, weight, weight), scattered));
+        }
+
+        // Into the coating through its mean surface. Fresnel factors are
+        // accounted for by the choices between reflection and refraction.
+        let mut direction = refract(&unit_direction, &rec.normal, 1. / self.index_of_refraction);
+        let mut attenuation = Color::new(1., 1., 1.);
+        for _ in 0..MAX_BOUNCES {
+            attenuation = &attenuation * &self.transmittance(direction.dot(&rec.normal));
+            let inner = Ray::new(rec.p.clone(), direction, r_in.time());
+            let (base_attenuation, scattered) = self.base.scatter(&inner, rec)?;
+            attenuation = &attenuation * &base_attenuation;
+
+            let up = scattered.direction().clone().normalize();
+            let cos_up = up.dot(&rec.normal);
+            // Let through by the base
+            if cos_up <= 0. {
+                return Some((attenuation, scattered));
+            }
+
+            attenuation = &attenuation * &self.transmittance(cos_up);
+            if rng.gen::<f64>() < fresnel_dielectric(cos_up, 1. / self.index_of_refraction) {
+                direction = reflect(&up, &rec.normal);
+            } else {
+                let out = refract(&up, &-rec.normal.clone(), self.index_of_refraction);
+                return Some((attenuation, Ray::new(rec.p.clone(), out, r_in.time())));
+            }
+        }
+        None
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        let cos_o = wo.dot(&rec.normal);
+        if cos_o <= 0. || self.base.is_specular() {
+            return Color::new(0., 0., 0.);
+        }
+        let (through, _) = self.walk(wo, wi, rec);
+        let through = (1. - fresnel_dielectric(cos_o, self.index_of_refraction)) * &through;
+
+        let frame = Onb::build_from_hit(rec);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wi.z <= 0. {
+            return through;
+        }
+        let h = (&wo + &wi).normalize();
+        let fresnel = fresnel_dielectric(wo.dot(&h), self.index_of_refraction);
+        let top =
+            self.distribution.d(&h) * self.distribution.g(&wo, &wi) * fresnel / (4. * wo.z * wi.z);
+        through + Color::new(top, top, top)
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        let cos_o = wo.dot(&rec.normal);
+        if cos_o <= 0. || self.base.is_specular() {
+            return 0.;
+        }
+        let fresnel = fresnel_dielectric(cos_o, self.index_of_refraction);
+        let (_, through) = self.walk(wo, wi, rec);
+        let through = (1. - fresnel) * through;
+
+        let frame = Onb::build_from_hit(rec);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wi.z <= 0. {
+            return through;
+        }
+        let h = (&wo + &wi).normalize();
+        fresnel * self.distribution.pdf_visible(&wo, &h) / (4. * wo.dot(&h)) + through
+    }
+
+    // Only a specular base leaves nothing to connect to
+    fn is_specular(&self) -> bool {
+        self.base.is_specular()
+    }
+}
@@ -1,5 +1,6 @@
 use crate::vec3::Color;
 use std::fmt;
+use std::io::{self, Write};
 
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -10,3 +11,33 @@ impl fmt::Display for Color {
         write!(f, "{} {} {}", r, g, b)
     }
 }
+
+// Writes colors without gamma correction, for data that is already in display space
+pub struct Linear<'a>(pub &'a Color);
+
+impl fmt::Display for Linear<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = (self.0.x * 255.999) as u8;
+        let g = (self.0.y * 255.999) as u8;
+        let b = (self.0.z * 255.999) as u8;
+        write!(f, "{} {} {}", r, g, b)
+    }
+}
+
+pub fn write_ppm(
+    out: &mut impl Write,
+    image_width: usize,
+    image_height: usize,
+    pixel_colors: &[Color],
+    gamma_correct: bool,
+) -> io::Result<()> {
+    writeln!(out, "P3\n{} {}\n255", image_width, image_height)?;
+    for pixel_color in pixel_colors {
+        if gamma_correct {
+            writeln!(out, "{}", pixel_color)?;
+        } else {
+            writeln!(out, "{}", Linear(pixel_color))?;
+        }
+    }
+    Ok(())
+}
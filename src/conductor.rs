@@ -0,0 +1,108 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::microfacet::{fresnel_conductor, Ggx};
+use crate::onb::Onb;
+use crate::Color;
+use crate::Ray;
+use crate::Vec3;
+use rand::Rng;
+
+// Rough metal with a GGX distribution of mirror microfacets, reflecting
+// by the Fresnel equations of its complex index of refraction eta + i k, given
+// per RGB channel at about 650, 550 and 450 nm.
+pub struct Conductor {
+    eta: Color,
+    k: Color,
+    distribution: Ggx,
+}
+
+impl Conductor {
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: Ggx::new(roughness),
+        }
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Conductor::new(
+            Color::new(0.143, 0.374, 1.442),
+            Color::new(3.983, 2.386, 1.603),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Conductor::new(
+            Color::new(0.200, 0.924, 1.102),
+            Color::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Conductor::new(
+            Color::new(0.155, 0.117, 0.138),
+            Color::new(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    pub fn aluminum(roughness: f64) -> Self {
+        Conductor::new(
+            Color::new(1.657, 0.880, 0.521),
+            Color::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let mut rng = rand::thread_rng();
+        let frame = Onb::build_from_hit(rec);
+        let wo = frame.to_local(&-r_in.direction().clone().normalize());
+        if wo.z <= 0. {
+            return None;
+        }
+
+        let h = self.distribution.sample_visible(&wo, rng.gen(), rng.gen());
+        let wi = 2. * wo.dot(&h) * &h - wo.clone();
+        // Reflected below the surface, lost with single scattering microfacets
+        if wi.z <= 0. {
+            return None;
+        }
+
+        // f cos / pdf, with most of the terms cancelling out
+        let fresnel = fresnel_conductor(wo.dot(&h), &self.eta, &self.k);
+        let attenuation = (self.distribution.g(&wo, &wi) / self.distribution.g1(&wo)) * &fresnel;
+        let scattered = Ray::new(rec.p.clone(), frame.local(wi.x, wi.y, wi.z), r_in.time());
+        Some((attenuation, scattered))
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        let frame = Onb::build_from_hit(rec);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z <= 0. || wi.z <= 0. {
+            return Color::new(0., 0., 0.);
+        }
+        let h = (&wo + &wi).normalize();
+        let fresnel = fresnel_conductor(wo.dot(&h), &self.eta, &self.k);
+        (self.distribution.d(&h) * self.distribution.g(&wo, &wi) / (4. * wo.z * wi.z)) * &fresnel
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        let frame = Onb::build_from_hit(rec);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z <= 0. || wi.z <= 0. {
+            return 0.;
+        }
+        let h = (&wo + &wi).normalize();
+        self.distribution.pdf_visible(&wo, &h) / (4. * wo.dot(&h))
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
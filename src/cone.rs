@@ -0,0 +1,93 @@
+use crate::aabb::Aabb;
+use crate::disk::Disk;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::roots;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+// Cone with its capped base on base and its apex height above, along the Y axis.
+// On the side, u: angle around the axis, v: height over the total height.
+pub struct Cone {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    cap: Disk,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Cone {
+    pub fn new(
+        base: Point3,
+        radius: f64,
+        height: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Cone {
+        let cap = Disk::new(
+            base.clone(),
+            Vec3::new(0.0, -1.0, 0.0),
+            radius,
+            mat_ptr.clone(),
+        );
+        Cone {
+            base,
+            radius,
+            height,
+            cap,
+            mat_ptr,
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let o = r.origin() - &self.base;
+        let d = r.direction();
+
+        // x^2 + z^2 = (k * (height - y))^2
+        let k2 = (self.radius / self.height).powi(2);
+        let h = self.height - o.y;
+        let side = roots::quadratic(
+            d.x * d.x + d.z * d.z - k2 * d.y * d.y,
+            2.0 * (o.x * d.x + o.z * d.z + k2 * h * d.y),
+            o.x * o.x + o.z * o.z - k2 * h * h,
+        )
+        .into_iter()
+        .filter(|&t| t_min <= t && t <= t_max)
+        .find(|&t| (0.0..=self.height).contains(&(o.y + t * d.y)))
+        .map(|t| {
+            let local = &o + t * d;
+            let rho = (local.x * local.x + local.z * local.z).sqrt();
+            let outward_normal = if rho > 0. {
+                Vec3::new(local.x, rho * self.radius / self.height, local.z).normalize()
+            } else {
+                Vec3::new(0.0, 1.0, 0.0)
+            };
+            let phi = (-local.z).atan2(local.x) + PI;
+            HitRecord::from_face_normal(
+                r,
+                r.at(t),
+                &outward_normal,
+                t,
+                (phi / (2.0 * PI), local.y / self.height),
+                self.mat_ptr.clone(),
+            )
+        });
+
+        let t_max = side.as_ref().map_or(t_max, |rec| rec.t);
+        self.cap.hit(r, t_min, t_max).or(side)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(Aabb::new(
+            &self.base - Vec3::new(self.radius, 0.0, self.radius),
+            &self.base + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::isotropic::Isotropic;
+use crate::material::{Material, MaterialIds};
+use crate::vec3::{Color, Vec3};
+use crate::Ray;
+use rand::Rng;
+use std::sync::Arc;
+
+// Homogeneous participating medium filling a closed boundary (e.g. fog or smoke).
+// Rays are scattered at an exponentially distributed distance inside it.
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable + Sync + Send + 'static>,
+    phase_function: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    neg_inv_density: f64,
+}
+
+impl ConstantMedium {
+    pub fn new(
+        boundary: impl Hittable + Sync + Send + 'static,
+        density: f64,
+        albedo: Color,
+    ) -> Self {
+        Self::with_phase_function(
+            boundary,
+            density,
+            Arc::new(Box::new(Isotropic::new(albedo))),
+        )
+    }
+
+    pub fn with_phase_function(
+        boundary: impl Hittable + Sync + Send + 'static,
+        density: f64,
+        phase_function: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Self {
+        ConstantMedium {
+            boundary: Box::new(boundary),
+            phase_function,
+            neg_inv_density: -1.0 / density,
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Entry and exit of the boundary along the whole line, so that rays
+        // starting inside the medium find an entry point behind them.
+        let rec1 = self.boundary.hit(r, -f64::INFINITY, f64::INFINITY)?;
+        let rec2 = self.boundary.hit(r, rec1.t + 0.0001, f64::INFINITY)?;
+
+        let t1 = rec1.t.max(t_min);
+        let t2 = rec2.t.min(t_max);
+        if t1 >= t2 {
+            return None;
+        }
+        let t1 = t1.max(0.0);
+
+        let ray_length = r.direction().length();
+        let distance_inside_boundary = (t2 - t1) * ray_length;
+        let hit_distance = self.neg_inv_density * rand::thread_rng().gen_range(0.0f64..1.0).ln();
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t1 + hit_distance / ray_length;
+        Some(HitRecord {
+            p: r.at(t),
+            // Arbitrary, media have no surface
+            normal: Vec3::new(1.0, 0.0, 0.0),
+            mat_ptr: self.phase_function.clone(),
+            t,
+            u: 0.0,
+            v: 0.0,
+            front_face: true,
+            object_id: None,
+            material_id: None,
+            tangent: None,
+            collision_weight: None,
+        })
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.boundary.bounding_box(time0, time1)
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.phase_function);
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.boundary.memory_size()
+    }
+}
@@ -0,0 +1,243 @@
+// Constructive solid geometry. Both children are closed solids; their
+// crossings along the ray are merged in order, and a crossing is kept where
+// it changes whether the ray is inside the combined solid.
+
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::MaterialIds;
+use crate::Ray;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    // Left minus right
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOp::Union => in_left || in_right,
+            CsgOp::Intersection => in_left && in_right,
+            CsgOp::Difference => in_left && !in_right,
+        }
+    }
+}
+
+pub struct Csg {
+    op: CsgOp,
+    left: Box<dyn Hittable + Sync + Send + 'static>,
+    right: Box<dyn Hittable + Sync + Send + 'static>,
+}
+
+impl Csg {
+    pub fn new(
+        op: CsgOp,
+        left: impl Hittable + Sync + Send + 'static,
+        right: impl Hittable + Sync + Send + 'static,
+    ) -> Self {
+        Csg {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn union(
+        left: impl Hittable + Sync + Send + 'static,
+        right: impl Hittable + Sync + Send + 'static,
+    ) -> Self {
+        Csg::new(CsgOp::Union, left, right)
+    }
+
+    pub fn intersection(
+        left: impl Hittable + Sync + Send + 'static,
+        right: impl Hittable + Sync + Send + 'static,
+    ) -> Self {
+        Csg::new(CsgOp::Intersection, left, right)
+    }
+
+    pub fn difference(
+        left: impl Hittable + Sync + Send + 'static,
+        right: impl Hittable + Sync + Send + 'static,
+    ) -> Self {
+        Csg::new(CsgOp::Difference, left, right)
+    }
+}
+
+// Whether the ray starts inside: its first crossing then leaves the solid
+fn starts_inside(hits: &[HitRecord]) -> bool {
+    hits.first().is_some_and(|rec| !rec.front_face)
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.all_hits(r, t_min, t_max).into_iter().next()
+    }
+
+    fn all_hits(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord> {
+        // Children are followed past t_max, a solid might only be left there
+        let left = self.left.all_hits(r, t_min, f64::INFINITY);
+        let right = self.right.all_hits(r, t_min, f64::INFINITY);
+
+        let mut in_left = starts_inside(&left);
+        let mut in_right = starts_inside(&right);
+        let mut inside = self.op.inside(in_left, in_right);
+
+        let mut hits = Vec::new();
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+        loop {
+            let from_left = match (left.peek(), right.peek()) {
+                (Some(a), Some(b)) => a.t <= b.t,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let mut rec = if from_left {
+                let rec = left.next().unwrap();
+                in_left = rec.front_face;
+                rec
+            } else {
+                let rec = right.next().unwrap();
+                in_right = rec.front_face;
+                rec
+            };
+            if rec.t > t_max {
+                break;
+            }
+
+            let now_inside = self.op.inside(in_left, in_right);
+            if now_inside != inside {
+                // The normal already faces the ray, only the side changes
+                rec.front_face = now_inside;
+                hits.push(rec);
+                inside = now_inside;
+            }
+        }
+        hits
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        match self.op {
+            CsgOp::Union => Some(Aabb::surrounding_box(
+                &self.left.bounding_box(time0, time1)?,
+                &self.right.bounding_box(time0, time1)?,
+            )),
+            CsgOp::Intersection => match (
+                self.left.bounding_box(time0, time1),
+                self.right.bounding_box(time0, time1),
+            ) {
+                (Some(a), Some(b)) => Some(Aabb::overlap(&a, &b)),
+                (a, b) => a.or(b),
+            },
+            CsgOp::Difference => self.left.bounding_box(time0, time1),
+        }
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        self.left.number_materials(ids);
+        self.right.number_materials(ids);
+    }
+
+    fn traversal_cost(&self, r: &Ray, t_min: f64, t_max: f64) -> usize {
+        self.left.traversal_cost(r, t_min, t_max) + self.right.traversal_cost(r, t_min, t_max)
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.left.memory_size() + self.right.memory_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lambertian::Lambertian;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::vec3::{Color, Point3, Vec3};
+    use std::sync::Arc;
+
+    // Unit spheres at x = -0.5 and 0.5, so the left one spans [-1.5, 0.5]
+    // and the right one [-0.5, 1.5] along the x axis
+    fn spheres() -> (Sphere, Sphere) {
+        let material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+            Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        (
+            Sphere::new(Point3::new(-0.5, 0.0, 0.0), 1.0, material.clone()),
+            Sphere::new(Point3::new(0.5, 0.0, 0.0), 1.0, material),
+        )
+    }
+
+    fn csg(op: CsgOp) -> Csg {
+        let (left, right) = spheres();
+        Csg::new(op, left, right)
+    }
+
+    // x of every crossing along +x from x = start, with whether it enters
+    fn crossings(csg: &Csg, start: f64) -> Vec<(f64, bool)> {
+        let direction = Vec3::new(1.0, 0.0, 0.0);
+        let r = Ray::new(Point3::new(start, 0.0, 0.0), direction.clone(), 0.0);
+        csg.all_hits(&r, 0.001, f64::INFINITY)
+            .into_iter()
+            .map(|rec| {
+                // Normals face the ray whichever side it is on
+                assert!(rec.normal.dot(&direction) < 0.0);
+                (start + rec.t, rec.front_face)
+            })
+            .collect()
+    }
+
+    fn assert_crossings(found: Vec<(f64, bool)>, expected: &[(f64, bool)]) {
+        assert_eq!(found.len(), expected.len(), "crossings {:?}", found);
+        for ((x, entering), (y, expected_entering)) in found.iter().zip(expected) {
+            assert!((x - y).abs() < 1e-9, "crossings {:?}", found);
+            assert_eq!(entering, expected_entering, "crossings {:?}", found);
+        }
+    }
+
+    #[test]
+    fn merges_intervals() {
+        assert_crossings(
+            crossings(&csg(CsgOp::Union), -5.0),
+            &[(-1.5, true), (1.5, false)],
+        );
+        assert_crossings(
+            crossings(&csg(CsgOp::Intersection), -5.0),
+            &[(-0.5, true), (0.5, false)],
+        );
+        // Leaves where the right sphere is entered, and enters where it is left
+        assert_crossings(
+            crossings(&csg(CsgOp::Difference), -5.0),
+            &[(-1.5, true), (-0.5, false)],
+        );
+        let (left, right) = spheres();
+        assert_crossings(
+            crossings(&Csg::difference(right, left), -5.0),
+            &[(0.5, true), (1.5, false)],
+        );
+    }
+
+    #[test]
+    fn starts_inside() {
+        assert_crossings(crossings(&csg(CsgOp::Union), 0.0), &[(1.5, false)]);
+        assert_crossings(crossings(&csg(CsgOp::Intersection), 0.0), &[(0.5, false)]);
+        // Inside the right sphere only, so outside the difference until it is left
+        assert!(crossings(&csg(CsgOp::Difference), 1.0).is_empty());
+    }
+
+    #[test]
+    fn first_hit_within_range() {
+        let csg = csg(CsgOp::Intersection);
+        let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let rec = csg.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.t - 4.5).abs() < 1e-9);
+        assert!(rec.front_face);
+        assert!(csg.hit(&r, 0.001, 4.0).is_none());
+        // The surface is only left beyond t_max
+        let rec = csg.hit(&r, 5.0, f64::INFINITY).unwrap();
+        assert!((rec.t - 5.5).abs() < 1e-9);
+        assert!(!rec.front_face);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::onb::Onb;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::sync::Arc;
+
+// Deepest subdivision of the curve when looking for a hit
+const MAX_DEPTH: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveMode {
+    // Flat strip that always faces the incoming ray, for thin fibers like hair
+    Ribbon,
+    // Round cross section, for thicker strands like grass
+    Tube,
+}
+
+fn lerp(t: f64, a: &Vec3, b: &Vec3) -> Vec3 {
+    (1.0 - t) * a + t * b
+}
+
+// Point and derivative of a cubic Bézier curve at u
+fn eval_bezier(cp: &[Vec3; 4], u: f64) -> (Vec3, Vec3) {
+    let cp1 = [
+        lerp(u, &cp[0], &cp[1]),
+        lerp(u, &cp[1], &cp[2]),
+        lerp(u, &cp[2], &cp[3]),
+    ];
+    let cp2 = [lerp(u, &cp1[0], &cp1[1]), lerp(u, &cp1[1], &cp1[2])];
+    (lerp(u, &cp2[0], &cp2[1]), 3.0 * (&cp2[1] - &cp2[0]))
+}
+
+// Point of the curve for blossom parameters (u0, u1, u2). (a, a, a) is the
+// point at a, the others give the control points of parts of the curve.
+fn blossom(cp: &[Vec3; 4], u0: f64, u1: f64, u2: f64) -> Vec3 {
+    let a = [
+        lerp(u0, &cp[0], &cp[1]),
+        lerp(u0, &cp[1], &cp[2]),
+        lerp(u0, &cp[2], &cp[3]),
+    ];
+    let b = [lerp(u1, &a[0], &a[1]), lerp(u1, &a[1], &a[2])];
+    lerp(u2, &b[0], &b[1])
+}
+
+// The two halves of the curve, split at u = 0.5
+fn subdivide(cp: &[Vec3; 4]) -> ([Vec3; 4], [Vec3; 4]) {
+    let mid = |a: &Vec3, b: &Vec3| 0.5 * (a + b);
+    let (p01, p12, p23) = (
+        mid(&cp[0], &cp[1]),
+        mid(&cp[1], &cp[2]),
+        mid(&cp[2], &cp[3]),
+    );
+    let (p012, p123) = (mid(&p01, &p12), mid(&p12, &p23));
+    let center = mid(&p012, &p123);
+    (
+        [cp[0].clone(), p01, p012, center.clone()],
+        [center, p123, p23, cp[3].clone()],
+    )
+}
+
+// Cubic Bézier curve swept by a width that varies linearly from its start to
+// its end. u runs along the curve and v across it, 0.5 on the center line.
+pub struct Curve {
+    // Of the part of the curve in u_range, when split
+    control_points: [Point3; 4],
+    u_range: (f64, f64),
+    widths: (f64, f64),
+    mode: CurveMode,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Curve {
+    pub fn new(
+        control_points: [Point3; 4],
+        widths: (f64, f64),
+        mode: CurveMode,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Self {
+        Curve {
+            control_points,
+            u_range: (0.0, 1.0),
+            widths,
+            mode,
+            mat_ptr,
+        }
+    }
+
+    // Parts of equal parameter length, each with its own tighter bounding box
+    pub fn split(self, segments: usize) -> impl Iterator<Item = Curve> {
+        (0..segments).map(move |k| {
+            let (u0, u1) = (k as f64 / segments as f64, (k + 1) as f64 / segments as f64);
+            let cp = &self.control_points;
+            Curve {
+                control_points: [
+                    blossom(cp, u0, u0, u0),
+                    blossom(cp, u0, u0, u1),
+                    blossom(cp, u0, u1, u1),
+                    blossom(cp, u1, u1, u1),
+                ],
+                u_range: (u0, u1),
+                widths: self.widths,
+                mode: self.mode,
+                mat_ptr: self.mat_ptr.clone(),
+            }
+        })
+    }
+
+    fn width(&self, u: f64) -> f64 {
+        (1.0 - u) * self.widths.0 + u * self.widths.1
+    }
+
+    fn max_width(&self) -> f64 {
+        self.width(self.u_range.0).max(self.width(self.u_range.1))
+    }
+
+    // Closest hit of the center line within half the width of the ray, with
+    // the curve in ray space: the ray starts at the origin and runs along +z
+    // at unit speed. Returns u, v, and the distance along the ray.
+    fn intersect(
+        &self,
+        cp: &[Vec3; 4],
+        (u0, u1): (f64, f64),
+        z_range: (f64, f64),
+        depth: i32,
+    ) -> Option<(f64, f64, f64)> {
+        // The curve lies within the convex hull of its control points
+        let half_width = 0.5 * self.width(u0).max(self.width(u1));
+        let (lo, hi) = cp.iter().fold(
+            (
+                Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+                Vec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(lo, hi), p| {
+                (
+                    Vec3::new(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z)),
+                    Vec3::new(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z)),
+                )
+            },
+        );
+        if lo.x - half_width > 0.0
+            || hi.x + half_width < 0.0
+            || lo.y - half_width > 0.0
+            || hi.y + half_width < 0.0
+            || lo.z - half_width > z_range.1
+            || hi.z + half_width < z_range.0
+        {
+            return None;
+        }
+
+        if depth > 0 {
+            let (first, second) = subdivide(cp);
+            let u_mid = 0.5 * (u0 + u1);
+            let near = self.intersect(&first, (u0, u_mid), z_range, depth - 1);
+            let z_max = near.map_or(z_range.1, |(_, _, z)| z);
+            let far = self.intersect(&second, (u_mid, u1), (z_range.0, z_max), depth - 1);
+            return far.or(near);
+        }
+
+        // Treat the segment as a straight line, and reject hits that belong to
+        // the neighbouring segments past its end points
+        if (cp[1].y - cp[0].y) * -cp[0].y + cp[0].x * (cp[0].x - cp[1].x) < 0.0
+            || (cp[2].y - cp[3].y) * -cp[3].y + cp[3].x * (cp[3].x - cp[2].x) < 0.0
+        {
+            return None;
+        }
+        let segment = Vec3::new(cp[3].x - cp[0].x, cp[3].y - cp[0].y, 0.0);
+        let denom = segment.norm();
+        if denom == 0.0 {
+            return None;
+        }
+        let w = ((-cp[0].x * segment.x - cp[0].y * segment.y) / denom).clamp(0.0, 1.0);
+        let u = (1.0 - w) * u0 + w * u1;
+        let hit_width = self.width(u);
+        let (pc, dpcdw) = eval_bezier(cp, w);
+        let distance_squared = pc.x * pc.x + pc.y * pc.y;
+        if distance_squared > 0.25 * hit_width * hit_width {
+            return None;
+        }
+
+        let distance = distance_squared.sqrt();
+        let z = match self.mode {
+            CurveMode::Ribbon => pc.z,
+            // Step back from the center line to the round surface
+            CurveMode::Tube => pc.z - (0.25 * hit_width * hit_width - distance_squared).sqrt(),
+        };
+        if z < z_range.0 || z > z_range.1 {
+            return None;
+        }
+        // Which side of the center line the ray passes on
+        let side = dpcdw.x * -pc.y + pc.x * dpcdw.y;
+        let v = if side > 0.0 {
+            0.5 + distance / hit_width
+        } else {
+            0.5 - distance / hit_width
+        };
+        Some((u, v, z))
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let length = r.direction().length();
+        let frame = Onb::build_from_w(r.direction());
+        let to_ray = |p: &Point3| {
+            let d = p - r.origin();
+            Vec3::new(d.dot(&frame.u), d.dot(&frame.v), d.dot(&frame.w))
+        };
+        let cp = [
+            to_ray(&self.control_points[0]),
+            to_ray(&self.control_points[1]),
+            to_ray(&self.control_points[2]),
+            to_ray(&self.control_points[3]),
+        ];
+
+        // Subdivide until the segments are within a fraction of the width of
+        // straight lines, judged by the second differences of the control points
+        let l0 = (0..2)
+            .map(|i| {
+                let d = &(&cp[i] - &(2.0 * &cp[i + 1])) + &cp[i + 2];
+                d.x.abs().max(d.y.abs()).max(d.z.abs())
+            })
+            .fold(0.0, f64::max);
+        let epsilon = 0.05 * self.max_width();
+        let depth = if l0 > 0.0 && epsilon > 0.0 {
+            ((2f64.sqrt() * 6.0 * l0 / (8.0 * epsilon)).log2() / 2.0)
+                .round()
+                .clamp(0.0, MAX_DEPTH as f64) as i32
+        } else {
+            0
+        };
+
+        let (u, v, z) =
+            self.intersect(&cp, self.u_range, (t_min * length, t_max * length), depth)?;
+        let t = z / length;
+        let p = r.at(t);
+        let (u0, u1) = self.u_range;
+        let (center, derivative) = eval_bezier(&self.control_points, (u - u0) / (u1 - u0));
+        let tangent = derivative.normalize();
+        let outward_normal = match self.mode {
+            // Towards the ray, across the fiber
+            CurveMode::Ribbon => -r.direction().clone(),
+            CurveMode::Tube => &p - &center,
+        };
+        let outward_normal =
+            (&outward_normal - &(outward_normal.dot(&tangent) * &tangent)).normalize();
+        let mut rec =
+            HitRecord::from_face_normal(r, p, &outward_normal, t, (u, v), self.mat_ptr.clone());
+        rec.tangent = Some(tangent);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let half_width = 0.5 * self.max_width();
+        let e = Vec3::new(half_width, half_width, half_width);
+        let bbox = self.control_points[1..].iter().fold(
+            Aabb::new(
+                self.control_points[0].clone(),
+                self.control_points[0].clone(),
+            ),
+            |acc, p| Aabb::surrounding_box(&acc, &Aabb::new(p.clone(), p.clone())),
+        );
+        Some(Aabb::new(&bbox.minimum - &e, &bbox.maximum + &e))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
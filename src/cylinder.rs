@@ -0,0 +1,95 @@
+use crate::aabb::Aabb;
+use crate::disk::Disk;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::roots;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+// Capped cylinder standing on base, along the Y axis.
+// On the side, u: angle around the axis, v: height over the total height.
+pub struct Cylinder {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    caps: [Disk; 2],
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Point3,
+        radius: f64,
+        height: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Cylinder {
+        let caps = [
+            Disk::new(
+                base.clone(),
+                Vec3::new(0.0, -1.0, 0.0),
+                radius,
+                mat_ptr.clone(),
+            ),
+            Disk::new(
+                &base + Vec3::new(0.0, height, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                radius,
+                mat_ptr.clone(),
+            ),
+        ];
+        Cylinder {
+            base,
+            radius,
+            height,
+            caps,
+            mat_ptr,
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let o = r.origin() - &self.base;
+        let d = r.direction();
+
+        let side = roots::quadratic(
+            d.x * d.x + d.z * d.z,
+            2.0 * (o.x * d.x + o.z * d.z),
+            o.x * o.x + o.z * o.z - self.radius.powi(2),
+        )
+        .into_iter()
+        .filter(|&t| t_min <= t && t <= t_max)
+        .find(|&t| (0.0..=self.height).contains(&(o.y + t * d.y)))
+        .map(|t| {
+            let local = &o + t * d;
+            let outward_normal = Vec3::new(local.x, 0.0, local.z) / self.radius;
+            let phi = (-local.z).atan2(local.x) + PI;
+            HitRecord::from_face_normal(
+                r,
+                r.at(t),
+                &outward_normal,
+                t,
+                (phi / (2.0 * PI), local.y / self.height),
+                self.mat_ptr.clone(),
+            )
+        });
+
+        self.caps.iter().fold(side, |closest, cap| {
+            let t_max = closest.as_ref().map_or(t_max, |rec| rec.t);
+            cap.hit(r, t_min, t_max).or(closest)
+        })
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(Aabb::new(
+            &self.base - Vec3::new(self.radius, 0.0, self.radius),
+            &self.base + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::aabb::Aabb;
+use crate::perlin::Perlin;
+use crate::vec3::Point3;
+
+// Spatially varying density of a heterogeneous medium, in world space
+pub trait DensityField {
+    fn density(&self, p: &Point3) -> f64;
+
+    // Upper bound of density over the whole field, the majorant for tracking
+    fn max_density(&self) -> f64;
+}
+
+// Densities sampled at nx * ny * nz points spread over a box, trilinearly
+// interpolated in between and zero outside.
+pub struct VoxelGrid {
+    bounds: Aabb,
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    values: Vec<f64>,
+    max_density: f64,
+}
+
+impl VoxelGrid {
+    // values is indexed as x + nx * (y + ny * z)
+    pub fn new(bounds: Aabb, nx: usize, ny: usize, nz: usize, values: Vec<f64>) -> Self {
+        assert_eq!(values.len(), nx * ny * nz, "VoxelGrid size mismatch.");
+        assert!(
+            nx > 1 && ny > 1 && nz > 1,
+            "VoxelGrid needs 2 samples per axis."
+        );
+        let max_density = values.iter().fold(0.0_f64, |max, &v| max.max(v));
+        VoxelGrid {
+            bounds,
+            nx,
+            ny,
+            nz,
+            values,
+            max_density,
+        }
+    }
+
+    // Samples f at every grid point, f receives coordinates in [0, 1]^3
+    pub fn from_fn(
+        bounds: Aabb,
+        nx: usize,
+        ny: usize,
+        nz: usize,
+        f: impl Fn(f64, f64, f64) -> f64,
+    ) -> Self {
+        let values = (0..nz)
+            .flat_map(|z| (0..ny).flat_map(move |y| (0..nx).map(move |x| (x, y, z))))
+            .map(|(x, y, z)| {
+                f(
+                    x as f64 / (nx - 1) as f64,
+                    y as f64 / (ny - 1) as f64,
+                    z as f64 / (nz - 1) as f64,
+                )
+            })
+            .collect();
+        VoxelGrid::new(bounds, nx, ny, nz, values)
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.values[x + self.nx * (y + self.ny * z)]
+    }
+}
+
+impl DensityField for VoxelGrid {
+    fn density(&self, p: &Point3) -> f64 {
+        let extent = &self.bounds.maximum - &self.bounds.minimum;
+        let local = p - &self.bounds.minimum;
+        let (gx, gy, gz) = (
+            local.x / extent.x * (self.nx - 1) as f64,
+            local.y / extent.y * (self.ny - 1) as f64,
+            local.z / extent.z * (self.nz - 1) as f64,
+        );
+        if gx < 0.
+            || gy < 0.
+            || gz < 0.
+            || gx > (self.nx - 1) as f64
+            || gy > (self.ny - 1) as f64
+            || gz > (self.nz - 1) as f64
+        {
+            return 0.0;
+        }
+
+        let (x0, y0, z0) = (
+            (gx as usize).min(self.nx - 2),
+            (gy as usize).min(self.ny - 2),
+            (gz as usize).min(self.nz - 2),
+        );
+        let (fx, fy, fz) = (gx - x0 as f64, gy - y0 as f64, gz - z0 as f64);
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+        let c00 = lerp(self.value(x0, y0, z0), self.value(x0 + 1, y0, z0), fx);
+        let c10 = lerp(
+            self.value(x0, y0 + 1, z0),
+            self.value(x0 + 1, y0 + 1, z0),
+            fx,
+        );
+        let c01 = lerp(
+            self.value(x0, y0, z0 + 1),
+            self.value(x0 + 1, y0, z0 + 1),
+            fx,
+        );
+        let c11 = lerp(
+            self.value(x0, y0 + 1, z0 + 1),
+            self.value(x0 + 1, y0 + 1, z0 + 1),
+            fx,
+        );
+        lerp(lerp(c00, c10, fy), lerp(c01, c11, fy), fz)
+    }
+
+    fn max_density(&self) -> f64 {
+        self.max_density
+    }
+}
+
+// Billowy procedural density from Perlin turbulence, for clouds
+pub struct NoiseDensity {
+    noise: Perlin,
+    scale: f64,
+    octaves: usize,
+    density: f64,
+    // Turbulence below this value is empty space, which breaks the field into puffs
+    threshold: f64,
+}
+
+impl NoiseDensity {
+    pub fn new(scale: f64, octaves: usize, density: f64, threshold: f64) -> Self {
+        NoiseDensity {
+            noise: Perlin::new(),
+            scale,
+            octaves,
+            density,
+            threshold,
+        }
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, p: &Point3) -> f64 {
+        let turb = self.noise.turb(&(self.scale * p), self.octaves);
+        self.density * (turb - self.threshold).max(0.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        // turb is below 2
+        self.density * (2.0 - self.threshold).max(0.0)
+    }
+}
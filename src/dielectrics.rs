@@ -1,6 +1,7 @@
 use rand::Rng;
 
 use crate::ray::{reflect, refract};
+use crate::spectrum::Wavelengths;
 use crate::{hittable::HitRecord, material::Material, ray::Ray, vec3::Color};
 
 fn reflectance(cosine: f64, ref_index: f64) -> f64 {
@@ -10,25 +11,91 @@ fn reflectance(cosine: f64, ref_index: f64) -> f64 {
     r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
 }
 
+// Where the index of a dispersive glass is quoted, the sodium d-line, used
+// when rendering in RGB
+const REFERENCE_WAVELENGTH: f64 = 587.6;
+
+// Index of refraction as a function of the wavelength in micrometers
+enum Dispersion {
+    // n = a + b / lambda^2
+    Cauchy { a: f64, b: f64 },
+    // n^2 = 1 + sum b_i lambda^2 / (lambda^2 - c_i)
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    fn index(&self, wavelength: f64) -> f64 {
+        let l2 = (wavelength / 1000.0).powi(2);
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / l2,
+            Dispersion::Sellmeier { b, c } => {
+                (1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>()).sqrt()
+            }
+        }
+    }
+}
+
 pub struct Dielectrics {
     index_of_refraction: f64,
+    dispersion: Option<Dispersion>,
 }
 
 impl Dielectrics {
     pub fn new(index_of_refraction: f64) -> Self {
         Self {
             index_of_refraction,
+            dispersion: None,
         }
     }
-}
 
-impl Material for Dielectrics {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn dispersive(dispersion: Dispersion) -> Self {
+        Self {
+            index_of_refraction: dispersion.index(REFERENCE_WAVELENGTH),
+            dispersion: Some(dispersion),
+        }
+    }
+
+    // b in square micrometers
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Dielectrics::dispersive(Dispersion::Cauchy { a, b })
+    }
+
+    // c in square micrometers
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Dielectrics::dispersive(Dispersion::Sellmeier { b, c })
+    }
+
+    // Schott N-BK7, common optical glass
+    pub fn bk7() -> Self {
+        Dielectrics::sellmeier(
+            [1.03961212, 0.231792344, 1.01046945],
+            [0.00600069867, 0.0200179144, 103.560653],
+        )
+    }
+
+    // Schott SF11, a dense flint glass that disperses strongly
+    pub fn dense_flint() -> Self {
+        Dielectrics::sellmeier(
+            [1.73759695, 0.313747346, 1.89878101],
+            [0.013188707, 0.0623068142, 155.23629],
+        )
+    }
+
+    pub fn diamond() -> Self {
+        Dielectrics::sellmeier([0.3306, 4.3356, 0.0], [0.030625, 0.011236, 0.0])
+    }
+
+    fn scatter_with_index(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        index_of_refraction: f64,
+    ) -> Option<(Color, Ray)> {
         let attenuation = Color::new(1.0, 1.0, 1.0);
         let refraction_ratio = if rec.front_face {
-            1.0 / self.index_of_refraction
+            1.0 / index_of_refraction
         } else {
-            self.index_of_refraction
+            index_of_refraction
         };
 
         let unit_direction = r_in.direction().clone().normalize();
@@ -48,8 +115,31 @@ impl Material for Dielectrics {
             refract(&unit_direction, &rec.normal, refraction_ratio)
         };
 
-        let scattered = Ray::new(rec.p.clone(), direction);
+        let scattered = Ray::new(rec.p.clone(), direction, r_in.time());
 
         Some((attenuation, scattered))
     }
 }
+
+impl Material for Dielectrics {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        self.scatter_with_index(r_in, rec, self.index_of_refraction)
+    }
+
+    // Dispersion bends each wavelength its own way, so the path follows the
+    // hero wavelength alone
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: &mut Wavelengths,
+    ) -> Option<(Color, Ray)> {
+        match &self.dispersion {
+            Some(dispersion) => {
+                wavelengths.terminate_secondary();
+                self.scatter_with_index(r_in, rec, dispersion.index(wavelengths.hero()))
+            }
+            None => self.scatter(r_in, rec),
+        }
+    }
+}
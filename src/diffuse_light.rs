@@ -0,0 +1,29 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::Color;
+use crate::Ray;
+
+// Emits on the outward side only and absorbs everything that hits it
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<(Color, Ray)> {
+        None
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        if rec.front_face {
+            self.emit.clone()
+        } else {
+            Color::new(0., 0., 0.)
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::onb::Onb;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+// Flat disk facing normal.
+// u: angle around the normal, v: distance from the center over the radius.
+pub struct Disk {
+    center: Point3,
+    radius: f64,
+    axes: Onb,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Disk {
+    pub fn new(
+        center: Point3,
+        normal: Vec3,
+        radius: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Disk {
+        Disk {
+            center,
+            radius,
+            axes: Onb::build_from_w(&normal),
+            mat_ptr,
+        }
+    }
+
+    fn uv(&self, p: &Point3) -> (f64, f64) {
+        let local = p - &self.center;
+        let phi = local.dot(&self.axes.v).atan2(local.dot(&self.axes.u)) + PI;
+        (phi / (2.0 * PI), local.length() / self.radius)
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let normal = &self.axes.w;
+        let denom = normal.dot(r.direction());
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = normal.dot(&(&self.center - r.origin())) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let p = r.at(t);
+        if (&p - &self.center).norm() > self.radius.powi(2) {
+            return None;
+        }
+        let uv = self.uv(&p);
+        Some(HitRecord::from_face_normal(
+            r,
+            p,
+            normal,
+            t,
+            uv,
+            self.mat_ptr.clone(),
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        // Extent of the rim along each axis
+        let n = &self.axes.w;
+        let e = Vec3::new(
+            self.radius * (1.0 - n.x * n.x).max(0.0).sqrt(),
+            self.radius * (1.0 - n.y * n.y).max(0.0).sqrt(),
+            self.radius * (1.0 - n.z * n.z).max(0.0).sqrt(),
+        );
+        Some(Aabb::new(&self.center - &e, &self.center + &e).pad(0.0001))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+
+    fn sample_surface(&self) -> Option<HitRecord> {
+        let mut rng = rand::thread_rng();
+        let r = self.radius * rng.gen_range(0.0f64..1.0).sqrt();
+        let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+        let p = &self.center + self.axes.local(r * phi.cos(), r * phi.sin(), 0.0);
+        let (u, v) = self.uv(&p);
+        Some(HitRecord {
+            p,
+            normal: self.axes.w.clone(),
+            mat_ptr: self.mat_ptr.clone(),
+            t: 0.0,
+            u,
+            v,
+            front_face: true,
+            object_id: None,
+            material_id: None,
+            tangent: None,
+            collision_weight: None,
+        })
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius.powi(2)
+    }
+}
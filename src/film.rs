@@ -0,0 +1,56 @@
+use crate::vec3::Color;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Accumulates contributions that land on arbitrary pixels (light tracing),
+// shared between all render threads.
+pub struct Film {
+    image_width: usize,
+    image_height: usize,
+    splats: Vec<[AtomicU64; 3]>,
+}
+
+impl Film {
+    pub fn new(image_width: usize, image_height: usize) -> Self {
+        let splats = (0..image_width * image_height)
+            .map(|_| [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)])
+            .collect();
+        Film {
+            image_width,
+            image_height,
+            splats,
+        }
+    }
+
+    // u, v are image coordinates as passed to Camera::get_ray
+    pub fn add_splat(&self, u: f64, v: f64, color: &Color) {
+        if !(color.x.is_finite() && color.y.is_finite() && color.z.is_finite()) {
+            return;
+        }
+        let i = ((u * self.image_width as f64) as usize).min(self.image_width - 1);
+        let j = ((v * self.image_height as f64) as usize).min(self.image_height - 1);
+        // Rows are stored top to bottom, like the output image
+        let pixel = &self.splats[(self.image_height - 1 - j) * self.image_width + i];
+        atomic_add(&pixel[0], color.x);
+        atomic_add(&pixel[1], color.y);
+        atomic_add(&pixel[2], color.z);
+    }
+
+    // Splatted colors in output order, multiplied by scale
+    pub fn splats(&self, scale: f64) -> Vec<Color> {
+        self.splats
+            .iter()
+            .map(|pixel| {
+                let load = |c: &AtomicU64| f64::from_bits(c.load(Ordering::Relaxed)) * scale;
+                Color::new(load(&pixel[0]), load(&pixel[1]), load(&pixel[2]))
+            })
+            .collect()
+    }
+}
+
+fn atomic_add(target: &AtomicU64, value: f64) {
+    target
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+            Some((f64::from_bits(bits) + value).to_bits())
+        })
+        .unwrap();
+}
@@ -0,0 +1,382 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::microfacet::fresnel_dielectric;
+use crate::onb::Onb;
+use crate::vec3::{Color, Vec3};
+use crate::Ray;
+use rand::Rng;
+use std::f64::consts::{LN_2, PI};
+
+// Lobes that are modelled one by one: R, TT and TRT. Longer paths are lumped
+// together into a last, isotropic lobe.
+const P_MAX: usize = 3;
+
+// Absorption per unit of fiber diameter of the two melanin pigments
+const EUMELANIN: (f64, f64, f64) = (0.419, 0.697, 1.37);
+const PHEOMELANIN: (f64, f64, f64) = (0.187, 0.4, 1.05);
+
+fn safe_sqrt(x: f64) -> f64 {
+    x.max(0.0).sqrt()
+}
+
+fn safe_asin(x: f64) -> f64 {
+    x.clamp(-1.0, 1.0).asin()
+}
+
+// Modified Bessel function of the first kind, order zero
+fn i0(x: f64) -> f64 {
+    let x2 = x * x / 4.0;
+    let (sum, _) = (1..10).fold((1.0, 1.0), |(sum, term), i| {
+        let term = term * x2 / (i * i) as f64;
+        (sum + term, term)
+    });
+    sum
+}
+
+fn log_i0(x: f64) -> f64 {
+    if x > 12.0 {
+        x + 0.5 * (-(2.0 * PI).ln() + (1.0 / x).ln() + 1.0 / (8.0 * x))
+    } else {
+        i0(x).ln()
+    }
+}
+
+// Longitudinal scattering of d'Eon et al.
+fn mp(cos_theta_i: f64, cos_theta_o: f64, sin_theta_i: f64, sin_theta_o: f64, v: f64) -> f64 {
+    let a = cos_theta_i * cos_theta_o / v;
+    let b = sin_theta_i * sin_theta_o / v;
+    if v <= 0.1 {
+        (log_i0(a) - b - 1.0 / v + LN_2 + (1.0 / (2.0 * v)).ln()).exp()
+    } else {
+        (-b).exp() * i0(a) / ((1.0 / v).sinh() * 2.0 * v)
+    }
+}
+
+fn logistic(x: f64, s: f64) -> f64 {
+    let x = x.abs();
+    (-x / s).exp() / (s * (1.0 + (-x / s).exp()).powi(2))
+}
+
+fn logistic_cdf(x: f64, s: f64) -> f64 {
+    1.0 / (1.0 + (-x / s).exp())
+}
+
+fn trimmed_logistic(x: f64, s: f64, a: f64, b: f64) -> f64 {
+    logistic(x, s) / (logistic_cdf(b, s) - logistic_cdf(a, s))
+}
+
+fn sample_trimmed_logistic(u: f64, s: f64, a: f64, b: f64) -> f64 {
+    let k = logistic_cdf(b, s) - logistic_cdf(a, s);
+    let x = -s * (1.0 / (u * k + logistic_cdf(a, s)) - 1.0).ln();
+    x.clamp(a, b)
+}
+
+// Azimuthal angle at which lobe p leaves
+fn phi(p: usize, gamma_o: f64, gamma_t: f64) -> f64 {
+    2.0 * p as f64 * gamma_t - 2.0 * gamma_o + p as f64 * PI
+}
+
+// Azimuthal scattering, a logistic around the exit angle of lobe p
+fn np(phi_difference: f64, p: usize, s: f64, gamma_o: f64, gamma_t: f64) -> f64 {
+    let mut dphi = phi_difference - phi(p, gamma_o, gamma_t);
+    while dphi > PI {
+        dphi -= 2.0 * PI;
+    }
+    while dphi < -PI {
+        dphi += 2.0 * PI;
+    }
+    trimmed_logistic(dphi, s, -PI, PI)
+}
+
+fn exp(c: &Color) -> Color {
+    Color::new(c.x.exp(), c.y.exp(), c.z.exp())
+}
+
+// Hair fiber scattering after Chiang et al., with the longitudinal lobes of
+// d'Eon et al.: a rough dielectric cylinder with absorbing interior and
+// cuticle scales that tilt the lobes. Needs hits with a tangent, from curves;
+// directions are taken relative to the fiber, with the offset across it from
+// v, so the camera facing ribbons of curves fit it best.
+pub struct Hair {
+    sigma_a: Color,
+    eta: f64,
+    beta_m: f64,
+    beta_n: f64,
+    alpha: f64,
+}
+
+impl Hair {
+    pub fn new(sigma_a: Color) -> Self {
+        Hair {
+            sigma_a,
+            eta: 1.55,
+            beta_m: 0.3,
+            beta_n: 0.3,
+            alpha: 2.0,
+        }
+    }
+
+    // Absorption from the concentrations of the pigments, from about 0 for
+    // blond to 8 for black hair for eumelanin, pheomelanin makes it redder
+    pub fn from_melanin(eumelanin: f64, pheomelanin: f64) -> Self {
+        Hair::new(Color::new(
+            eumelanin * EUMELANIN.0 + pheomelanin * PHEOMELANIN.0,
+            eumelanin * EUMELANIN.1 + pheomelanin * PHEOMELANIN.1,
+            eumelanin * EUMELANIN.2 + pheomelanin * PHEOMELANIN.2,
+        ))
+    }
+
+    // Longitudinal and azimuthal roughness, in [0, 1]
+    pub fn with_roughness(mut self, beta_m: f64, beta_n: f64) -> Self {
+        self.beta_m = beta_m;
+        self.beta_n = beta_n;
+        self
+    }
+
+    // Tilt of the cuticle scales in degrees
+    pub fn with_scale_angle(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    // Parameters of the fiber at a hit
+    fn lobes(&self, rec: &HitRecord) -> Lobes {
+        let frame = Onb::build_from_hit(rec);
+
+        let beta_m = self.beta_m;
+        let v0 = (0.726 * beta_m + 0.812 * beta_m.powi(2) + 3.7 * beta_m.powi(20)).powi(2);
+        let beta_n = self.beta_n;
+        let s =
+            (PI / 8.0).sqrt() * (0.265 * beta_n + 1.194 * beta_n.powi(2) + 5.372 * beta_n.powi(22));
+
+        let mut sin_2k_alpha = [self.alpha.to_radians().sin(), 0.0, 0.0];
+        let mut cos_2k_alpha = [safe_sqrt(1.0 - sin_2k_alpha[0].powi(2)), 0.0, 0.0];
+        for i in 1..3 {
+            sin_2k_alpha[i] = 2.0 * cos_2k_alpha[i - 1] * sin_2k_alpha[i - 1];
+            cos_2k_alpha[i] = cos_2k_alpha[i - 1].powi(2) - sin_2k_alpha[i - 1].powi(2);
+        }
+
+        let h = (2.0 * rec.v - 1.0).clamp(-1.0, 1.0);
+        Lobes {
+            frame,
+            h,
+            gamma_o: safe_asin(h),
+            eta: self.eta,
+            sigma_a: self.sigma_a.clone(),
+            v: [v0, 0.25 * v0, 4.0 * v0, 4.0 * v0],
+            s,
+            sin_2k_alpha,
+            cos_2k_alpha,
+        }
+    }
+}
+
+struct Lobes {
+    // w is the normal, u runs along the fiber
+    frame: Onb,
+    // Offset across the fiber in [-1, 1]
+    h: f64,
+    gamma_o: f64,
+    eta: f64,
+    sigma_a: Color,
+    // Longitudinal variance of every lobe
+    v: [f64; P_MAX + 1],
+    // Azimuthal logistic scale
+    s: f64,
+    sin_2k_alpha: [f64; 3],
+    cos_2k_alpha: [f64; 3],
+}
+
+impl Lobes {
+    // sin and cos of the longitudinal angle, and the azimuth
+    fn angles(w: &Vec3) -> (f64, f64, f64) {
+        let sin_theta = w.x.clamp(-1.0, 1.0);
+        (
+            sin_theta,
+            safe_sqrt(1.0 - sin_theta * sin_theta),
+            w.z.atan2(w.y),
+        )
+    }
+
+    // Outgoing angle tilted by the cuticle scales for lobe p
+    fn tilt(&self, p: usize, sin_theta_o: f64, cos_theta_o: f64) -> (f64, f64) {
+        let (sin_a, cos_a) = (&self.sin_2k_alpha, &self.cos_2k_alpha);
+        let (sin_op, cos_op) = match p {
+            0 => (
+                sin_theta_o * cos_a[1] - cos_theta_o * sin_a[1],
+                cos_theta_o * cos_a[1] + sin_theta_o * sin_a[1],
+            ),
+            1 => (
+                sin_theta_o * cos_a[0] + cos_theta_o * sin_a[0],
+                cos_theta_o * cos_a[0] - sin_theta_o * sin_a[0],
+            ),
+            2 => (
+                sin_theta_o * cos_a[2] + cos_theta_o * sin_a[2],
+                cos_theta_o * cos_a[2] - sin_theta_o * sin_a[2],
+            ),
+            _ => (sin_theta_o, cos_theta_o),
+        };
+        (sin_op, cos_op.abs())
+    }
+
+    // Refracted azimuth inside the fiber and the transmittance of one pass
+    fn interior(&self, sin_theta_o: f64, cos_theta_o: f64) -> (f64, Color) {
+        let sin_theta_t = sin_theta_o / self.eta;
+        let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+        let etap = (self.eta * self.eta - sin_theta_o * sin_theta_o).sqrt() / cos_theta_o;
+        let sin_gamma_t = self.h / etap;
+        let cos_gamma_t = safe_sqrt(1.0 - sin_gamma_t * sin_gamma_t);
+        let transmittance = exp(&(-(2.0 * cos_gamma_t / cos_theta_t) * &self.sigma_a));
+        (safe_asin(sin_gamma_t), transmittance)
+    }
+
+    // Attenuation of every lobe
+    fn ap(&self, cos_theta_o: f64, transmittance: &Color) -> [Color; P_MAX + 1] {
+        let cos_gamma_o = safe_sqrt(1.0 - self.h * self.h);
+        let f = fresnel_dielectric(cos_theta_o * cos_gamma_o, self.eta);
+        let mut ap = [
+            Color::new(f, f, f),
+            (1.0 - f).powi(2) * transmittance,
+            Color::new(0., 0., 0.),
+            Color::new(0., 0., 0.),
+        ];
+        for p in 2..P_MAX {
+            ap[p] = f * &(&ap[p - 1] * transmittance);
+        }
+        let tf = f * transmittance;
+        let remainder = Color::new(1.0 / (1.0 - tf.x), 1.0 / (1.0 - tf.y), 1.0 / (1.0 - tf.z));
+        ap[P_MAX] = &(&ap[P_MAX - 1] * &tf) * &remainder;
+        ap
+    }
+
+    // Probability of picking each lobe when sampling, by its mean attenuation
+    fn ap_pdf(&self, sin_theta_o: f64, cos_theta_o: f64) -> [f64; P_MAX + 1] {
+        let (_, transmittance) = self.interior(sin_theta_o, cos_theta_o);
+        let ap = self.ap(cos_theta_o, &transmittance);
+        let weights = [0, 1, 2, 3].map(|p| (ap[p].x + ap[p].y + ap[p].z) / 3.0);
+        let sum: f64 = weights.iter().sum();
+        weights.map(|w| w / sum)
+    }
+
+    fn f(&self, wo: &Vec3, wi: &Vec3) -> Color {
+        let (sin_theta_o, cos_theta_o, phi_o) = Self::angles(wo);
+        let (sin_theta_i, cos_theta_i, phi_i) = Self::angles(wi);
+        let (gamma_t, transmittance) = self.interior(sin_theta_o, cos_theta_o);
+        let ap = self.ap(cos_theta_o, &transmittance);
+        let phi_difference = phi_i - phi_o;
+
+        let mut sum = (0..P_MAX).fold(Color::new(0., 0., 0.), |sum, p| {
+            let (sin_op, cos_op) = self.tilt(p, sin_theta_o, cos_theta_o);
+            let m = mp(cos_theta_i, cos_op, sin_theta_i, sin_op, self.v[p]);
+            let n = np(phi_difference, p, self.s, self.gamma_o, gamma_t);
+            sum + (m * n) * &ap[p]
+        });
+        let m = mp(
+            cos_theta_i,
+            cos_theta_o,
+            sin_theta_i,
+            sin_theta_o,
+            self.v[P_MAX],
+        );
+        sum += &((m / (2.0 * PI)) * &ap[P_MAX]);
+
+        // The integrators multiply by the cosine to the normal, which is not
+        // part of fiber scattering
+        if wi.z.abs() > 0.0 {
+            sum / wi.z.abs()
+        } else {
+            sum
+        }
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        let (sin_theta_o, cos_theta_o, phi_o) = Self::angles(wo);
+        let (sin_theta_i, cos_theta_i, phi_i) = Self::angles(wi);
+        let (gamma_t, _) = self.interior(sin_theta_o, cos_theta_o);
+        let ap_pdf = self.ap_pdf(sin_theta_o, cos_theta_o);
+        let phi_difference = phi_i - phi_o;
+
+        let pdf: f64 = (0..P_MAX)
+            .map(|p| {
+                let (sin_op, cos_op) = self.tilt(p, sin_theta_o, cos_theta_o);
+                mp(cos_theta_i, cos_op, sin_theta_i, sin_op, self.v[p])
+                    * ap_pdf[p]
+                    * np(phi_difference, p, self.s, self.gamma_o, gamma_t)
+            })
+            .sum();
+        pdf + mp(
+            cos_theta_i,
+            cos_theta_o,
+            sin_theta_i,
+            sin_theta_o,
+            self.v[P_MAX],
+        ) * ap_pdf[P_MAX]
+            / (2.0 * PI)
+    }
+
+    // Picks a lobe, then the longitudinal and azimuthal angles within it
+    fn sample(&self, wo: &Vec3) -> Vec3 {
+        let mut rng = rand::thread_rng();
+        let (sin_theta_o, cos_theta_o, phi_o) = Self::angles(wo);
+        let ap_pdf = self.ap_pdf(sin_theta_o, cos_theta_o);
+
+        let mut u: f64 = rng.gen();
+        let mut p = 0;
+        while p < P_MAX && u >= ap_pdf[p] {
+            u -= ap_pdf[p];
+            p += 1;
+        }
+
+        let (sin_op, cos_op) = self.tilt(p, sin_theta_o, cos_theta_o);
+        let u1 = rng.gen::<f64>().max(1e-5);
+        let v = self.v[p];
+        let cos_theta = 1.0 + v * (u1 + (1.0 - u1) * (-2.0 / v).exp()).ln();
+        let sin_theta = safe_sqrt(1.0 - cos_theta * cos_theta);
+        let cos_phi = (2.0 * PI * rng.gen::<f64>()).cos();
+        let sin_theta_i = -cos_theta * sin_op + sin_theta * cos_phi * cos_op;
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+
+        let (gamma_t, _) = self.interior(sin_theta_o, cos_theta_o);
+        let dphi = if p < P_MAX {
+            phi(p, self.gamma_o, gamma_t) + sample_trimmed_logistic(rng.gen(), self.s, -PI, PI)
+        } else {
+            2.0 * PI * rng.gen::<f64>()
+        };
+        let phi_i = phi_o + dphi;
+        self.frame.local(
+            sin_theta_i,
+            cos_theta_i * phi_i.cos(),
+            cos_theta_i * phi_i.sin(),
+        )
+    }
+}
+
+impl Material for Hair {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let lobes = self.lobes(rec);
+        let wo = -r_in.direction().clone().normalize();
+        let wo_local = lobes.frame.to_local(&wo);
+        let wi = lobes.sample(&wo_local);
+        let wi_local = lobes.frame.to_local(&wi);
+        let pdf = lobes.pdf(&wo_local, &wi_local);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let weight = (wi_local.z.abs() / pdf) * &lobes.f(&wo_local, &wi_local);
+        Some((weight, Ray::new(rec.p.clone(), wi, r_in.time())))
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        let lobes = self.lobes(rec);
+        lobes.f(&lobes.frame.to_local(wo), &lobes.frame.to_local(wi))
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        let lobes = self.lobes(rec);
+        lobes.pdf(&lobes.frame.to_local(wo), &lobes.frame.to_local(wi))
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
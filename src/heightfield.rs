@@ -0,0 +1,314 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::texture::ImageTexture;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::sync::Arc;
+
+// Min and max height of every node at one level of the min-max mipmap.
+// Level 0 has one node per grid cell, each level above merges 2x2 nodes.
+struct Level {
+    nx: usize,
+    nz: usize,
+    ranges: Vec<(f64, f64)>,
+}
+
+impl Level {
+    fn range(&self, i: usize, j: usize) -> (f64, f64) {
+        self.ranges[i + self.nx * j]
+    }
+}
+
+// Terrain of nx * nz height samples spread over the x and z extent of bounds,
+// heights in [0, 1] spanning its y extent. Every grid cell is split into two
+// triangles with interpolated vertex normals. u follows x and v follows -z, so
+// that an image texture laid over it matches the image the heights came from.
+pub struct Heightfield {
+    bounds: Aabb,
+    nx: usize,
+    nz: usize,
+    heights: Vec<f64>,
+    normals: Vec<Vec3>,
+    levels: Vec<Level>,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Heightfield {
+    // heights is indexed as x + nx * z
+    pub fn new(
+        bounds: Aabb,
+        nx: usize,
+        nz: usize,
+        heights: Vec<f64>,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Self {
+        assert_eq!(heights.len(), nx * nz, "Heightfield size mismatch.");
+        assert!(nx > 1 && nz > 1, "Heightfield needs 2 samples per axis.");
+
+        let mut heightfield = Heightfield {
+            bounds,
+            nx,
+            nz,
+            heights,
+            normals: Vec::new(),
+            levels: Vec::new(),
+            mat_ptr,
+        };
+        heightfield.normals = (0..nz)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| heightfield.vertex_normal(i, j))
+            .collect();
+        heightfield.build_levels();
+        heightfield
+    }
+
+    // Heights from the brightness of a grayscale image, its top row at -z
+    pub fn from_image(
+        bounds: Aabb,
+        image: &ImageTexture,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Self {
+        let (nx, nz) = (image.width(), image.height());
+        let heights = (0..nz)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let c = image.pixel(i, j);
+                (c.x + c.y + c.z) / 3.0
+            })
+            .collect();
+        Heightfield::new(bounds, nx, nz, heights, mat_ptr)
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[i + self.nx * j]
+    }
+
+    fn cell_size(&self) -> (f64, f64) {
+        let extent = &self.bounds.maximum - &self.bounds.minimum;
+        (
+            extent.x / (self.nx - 1) as f64,
+            extent.z / (self.nz - 1) as f64,
+        )
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        let (dx, dz) = self.cell_size();
+        let (min, max) = (&self.bounds.minimum, &self.bounds.maximum);
+        Point3::new(
+            min.x + i as f64 * dx,
+            min.y + self.height(i, j) * (max.y - min.y),
+            min.z + j as f64 * dz,
+        )
+    }
+
+    fn vertex_uv(&self, i: usize, j: usize) -> (f64, f64) {
+        (
+            i as f64 / (self.nx - 1) as f64,
+            1.0 - j as f64 / (self.nz - 1) as f64,
+        )
+    }
+
+    // Slope by central differences, one sided on the border
+    fn vertex_normal(&self, i: usize, j: usize) -> Vec3 {
+        let (dx, dz) = self.cell_size();
+        let scale = self.bounds.maximum.y - self.bounds.minimum.y;
+        let (i0, i1) = (i.saturating_sub(1), (i + 1).min(self.nx - 1));
+        let (j0, j1) = (j.saturating_sub(1), (j + 1).min(self.nz - 1));
+        let slope_x = (self.height(i1, j) - self.height(i0, j)) * scale / ((i1 - i0) as f64 * dx);
+        let slope_z = (self.height(i, j1) - self.height(i, j0)) * scale / ((j1 - j0) as f64 * dz);
+        Vec3::new(-slope_x, 1.0, -slope_z).normalize()
+    }
+
+    fn build_levels(&mut self) {
+        let (cx, cz) = (self.nx - 1, self.nz - 1);
+        let ranges = (0..cz)
+            .flat_map(|j| (0..cx).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let corners = [
+                    self.height(i, j),
+                    self.height(i + 1, j),
+                    self.height(i, j + 1),
+                    self.height(i + 1, j + 1),
+                ];
+                corners
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &h| {
+                        (lo.min(h), hi.max(h))
+                    })
+            })
+            .collect();
+        self.levels.push(Level {
+            nx: cx,
+            nz: cz,
+            ranges,
+        });
+
+        while let Some(below) = self.levels.last().filter(|l| l.nx > 1 || l.nz > 1) {
+            let (nx, nz) = (below.nx.div_ceil(2), below.nz.div_ceil(2));
+            let ranges = (0..nz)
+                .flat_map(|j| (0..nx).map(move |i| (i, j)))
+                .map(|(i, j)| {
+                    Self::children(below, i, j).fold(
+                        (f64::INFINITY, f64::NEG_INFINITY),
+                        |(lo, hi), (ci, cj)| {
+                            let (child_lo, child_hi) = below.range(ci, cj);
+                            (lo.min(child_lo), hi.max(child_hi))
+                        },
+                    )
+                })
+                .collect();
+            self.levels.push(Level { nx, nz, ranges });
+        }
+    }
+
+    // Nodes of the level below that make up node (i, j)
+    fn children(below: &Level, i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (nx, nz) = (below.nx, below.nz);
+        (2 * j..(2 * j + 2).min(nz))
+            .flat_map(move |cj| (2 * i..(2 * i + 2).min(nx)).map(move |ci| (ci, cj)))
+    }
+
+    // Box of node (i, j) at level, padded so that flat nodes still get hit
+    fn node_box(&self, level: usize, i: usize, j: usize) -> Aabb {
+        let (dx, dz) = self.cell_size();
+        let cells = 1 << level;
+        let (min, max) = (&self.bounds.minimum, &self.bounds.maximum);
+        let (lo, hi) = self.levels[level].range(i, j);
+        Aabb::new(
+            Point3::new(
+                min.x + (i * cells) as f64 * dx,
+                min.y + lo * (max.y - min.y),
+                min.z + (j * cells) as f64 * dz,
+            ),
+            Point3::new(
+                min.x + ((i + 1) * cells).min(self.nx - 1) as f64 * dx,
+                min.y + hi * (max.y - min.y),
+                min.z + ((j + 1) * cells).min(self.nz - 1) as f64 * dz,
+            ),
+        )
+        .pad(1e-4)
+    }
+
+    // Descends the mipmap from a node whose box the ray hits, visiting children
+    // in the order the ray enters them
+    fn traverse(
+        &self,
+        r: &Ray,
+        level: usize,
+        (i, j): (usize, usize),
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord> {
+        if level == 0 {
+            return self.hit_cell(r, i, j, t_min, t_max);
+        }
+
+        // At most four children, kept on the stack
+        let mut children = [(0.0, (0, 0)); 4];
+        let mut count = 0;
+        for (ci, cj) in Self::children(&self.levels[level - 1], i, j) {
+            if let Some((entry, _)) = self.node_box(level - 1, ci, cj).interval(r, t_min, t_max) {
+                children[count] = (entry, (ci, cj));
+                count += 1;
+            }
+        }
+        let children = &mut children[..count];
+        children.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut closest = None;
+        let mut t_max = t_max;
+        for &(entry, child) in children.iter() {
+            if entry >= t_max {
+                break;
+            }
+            if let Some(rec) = self.traverse(r, level - 1, child, t_min, t_max) {
+                t_max = rec.t;
+                closest = Some(rec);
+            }
+        }
+        closest
+    }
+
+    fn hit_cell(&self, r: &Ray, i: usize, j: usize, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let triangles = [
+            [(i, j), (i + 1, j + 1), (i + 1, j)],
+            [(i, j), (i, j + 1), (i + 1, j + 1)],
+        ];
+        triangles
+            .iter()
+            .filter_map(|corners| self.hit_triangle(r, corners, t_min, t_max))
+            .min_by(|a, b| a.t.total_cmp(&b.t))
+    }
+
+    // Möller-Trumbore, with normal and uv interpolated from the corners
+    fn hit_triangle(
+        &self,
+        r: &Ray,
+        corners: &[(usize, usize); 3],
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord> {
+        let [(i0, j0), (i1, j1), (i2, j2)] = *corners;
+        let p0 = self.vertex(i0, j0);
+        let edge1 = self.vertex(i1, j1) - &p0;
+        let edge2 = self.vertex(i2, j2) - &p0;
+        let pvec = r.direction().cross(&edge2);
+        let det = edge1.dot(&pvec);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let tvec = r.origin() - &p0;
+        let b1 = tvec.dot(&pvec) * inv_det;
+        if !(0.0..=1.0).contains(&b1) {
+            return None;
+        }
+        let qvec = tvec.cross(&edge1);
+        let b2 = r.direction().dot(&qvec) * inv_det;
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return None;
+        }
+        let t = edge2.dot(&qvec) * inv_det;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let b0 = 1.0 - b1 - b2;
+        let normal = (b0 * &self.normals[i0 + self.nx * j0]
+            + b1 * &self.normals[i1 + self.nx * j1]
+            + b2 * &self.normals[i2 + self.nx * j2])
+            .normalize();
+        let uv = [(i0, j0, b0), (i1, j1, b1), (i2, j2, b2)].iter().fold(
+            (0.0, 0.0),
+            |(u, v), &(i, j, b)| {
+                let (vu, vv) = self.vertex_uv(i, j);
+                (u + b * vu, v + b * vv)
+            },
+        );
+        Some(HitRecord::from_face_normal(
+            r,
+            r.at(t),
+            &normal,
+            t,
+            uv,
+            self.mat_ptr.clone(),
+        ))
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let root = self.levels.len() - 1;
+        self.node_box(root, 0, 0).interval(r, t_min, t_max)?;
+        self.traverse(r, root, (0, 0), t_min, t_max)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(self.node_box(self.levels.len() - 1, 0, 0))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::Color;
+use crate::Ray;
+use crate::Vec3;
+use rand::Rng;
+use std::f64::consts::PI;
+
+// Anisotropic phase function. g > 0 scatters forward, g < 0 backward, g = 0 is isotropic.
+pub struct HenyeyGreenstein {
+    albedo: Color,
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Color, g: f64) -> Self {
+        Self {
+            albedo,
+            g: g.clamp(-0.99, 0.99),
+        }
+    }
+
+    // Density of scattering by an angle whose cosine is cos_theta
+    fn phase(&self, cos_theta: f64) -> f64 {
+        let denom = 1.0 + self.g.powi(2) - 2.0 * self.g * cos_theta;
+        (1.0 - self.g.powi(2)) / (4.0 * PI * denom * denom.sqrt())
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let mut rng = rand::thread_rng();
+        let g = self.g;
+        let xi = rng.gen_range(0.0..1.0);
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * xi
+        } else {
+            let sqr = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+            (1.0 + g * g - sqr * sqr) / (2.0 * g)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen_range(0.0..1.0);
+
+        let uvw = Onb::build_from_w(r_in.direction());
+        let direction = uvw.local(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        Some((
+            self.albedo.clone(),
+            Ray::new(rec.p.clone(), direction, r_in.time()),
+        ))
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, _rec: &HitRecord) -> Color {
+        self.phase(-wo.dot(wi)) * &self.albedo
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, _rec: &HitRecord) -> f64 {
+        self.phase(-wo.dot(wi))
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+}
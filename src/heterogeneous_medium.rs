@@ -0,0 +1,204 @@
+// Participating medium with a spatially varying density, sampled with
+// spectral tracking (Kutz et al. 2017), an unbiased variant of delta
+// tracking for per channel coefficients.
+//
+// Tentative collisions are placed at the rate of a constant majorant and
+// classified as scattering or null collisions, with probabilities weighted
+// by the path's current per channel weights. Absorption is folded into the
+// weights rather than terminating paths.
+//
+// `hit` can only return a record, so the per channel weights travel in the
+// record, applied by its material: real scattering returns a weighted phase
+// function, and a ray leaving the medium with weights other than one returns
+// a null record at the exit, whose material continues the ray unchanged.
+// Shadow rays see both as occluders, which keeps bdpt and the photon map
+// consistent because both are then reached by sampling only.
+// Other objects must not be placed inside the boundary.
+
+use crate::aabb::Aabb;
+use crate::density_field::DensityField;
+use crate::henyey_greenstein::HenyeyGreenstein;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::vec3::{Color, Point3, Vec3};
+use crate::Ray;
+use rand::Rng;
+use std::sync::Arc;
+
+pub struct HeterogeneousMedium {
+    boundary: Box<dyn Hittable + Sync + Send + 'static>,
+    density: Box<dyn DensityField + Sync + Send + 'static>,
+    // Coefficients per unit density
+    sigma_a: Color,
+    sigma_s: Color,
+    // Materials of the records returned by hit, the weights of which they apply
+    scattering: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    null_collision: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl HeterogeneousMedium {
+    pub fn new(
+        boundary: impl Hittable + Sync + Send + 'static,
+        density: impl DensityField + Sync + Send + 'static,
+        sigma_a: Color,
+        sigma_s: Color,
+        g: f64,
+    ) -> Self {
+        HeterogeneousMedium {
+            boundary: Box::new(boundary),
+            density: Box::new(density),
+            sigma_a,
+            sigma_s,
+            scattering: Arc::new(Box::new(Weighted {
+                phase_function: HenyeyGreenstein::new(Color::new(1.0, 1.0, 1.0), g),
+            })),
+            null_collision: Arc::new(Box::new(NullCollision)),
+        }
+    }
+
+    fn record(
+        &self,
+        p: Point3,
+        t: f64,
+        mat_ptr: &Arc<Box<dyn Material + Sync + Send>>,
+        weight: Color,
+    ) -> HitRecord {
+        HitRecord {
+            p,
+            // Arbitrary, media have no surface
+            normal: Vec3::new(1.0, 0.0, 0.0),
+            mat_ptr: mat_ptr.clone(),
+            t,
+            u: 0.0,
+            v: 0.0,
+            front_face: true,
+            object_id: None,
+            material_id: None,
+            tangent: None,
+            collision_weight: Some(weight),
+        }
+    }
+}
+
+fn sum(c: &Color) -> f64 {
+    c.x + c.y + c.z
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let rec1 = self.boundary.hit(r, -f64::INFINITY, f64::INFINITY)?;
+        let rec2 = self.boundary.hit(r, rec1.t + 0.0001, f64::INFINITY)?;
+
+        let t1 = rec1.t.max(t_min).max(0.0);
+        let t2 = rec2.t.min(t_max);
+        if t1 >= t2 {
+            return None;
+        }
+
+        let sigma_t = &self.sigma_a + &self.sigma_s;
+        let majorant = self.density.max_density() * sigma_t.x.max(sigma_t.y).max(sigma_t.z);
+        if majorant <= 0. {
+            return None;
+        }
+
+        let mut rng = rand::thread_rng();
+        let ray_length = r.direction().length();
+        let mut weight = Color::new(1.0, 1.0, 1.0);
+        let mut t = t1;
+        loop {
+            t -= (1.0 - rng.gen_range(0.0f64..1.0)).ln() / (majorant * ray_length);
+            if t >= t2 {
+                break;
+            }
+
+            let p = r.at(t);
+            let density = self.density.density(&p);
+            let scattering = density * &self.sigma_s;
+            let null = &Color::new(majorant, majorant, majorant) - &(density * &sigma_t);
+
+            // History aware collision probabilities; absorption is not sampled
+            let p_s = sum(&(&weight * &scattering));
+            let p_n = sum(&(&weight * &null));
+            if p_s + p_n <= 0. {
+                let absorbed = Color::new(0.0, 0.0, 0.0);
+                return Some(self.record(p, t, &self.null_collision, absorbed));
+            }
+
+            // Collision weights sigma / (majorant * probability of the event)
+            if rng.gen_range(0.0..p_s + p_n) < p_s {
+                weight = ((p_s + p_n) / (majorant * p_s)) * &(&weight * &scattering);
+                return Some(self.record(p, t, &self.scattering, weight));
+            }
+            weight = ((p_s + p_n) / (majorant * p_n)) * &(&weight * &null);
+        }
+
+        // Something closer than the exit was already found, it must not be skipped
+        if t2 < rec2.t || (weight.x == 1.0 && weight.y == 1.0 && weight.z == 1.0) {
+            None
+        } else {
+            Some(self.record(r.at(t2), t2, &self.null_collision, weight))
+        }
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.boundary.bounding_box(time0, time1)
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.scattering);
+        ids.id(&self.null_collision);
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.boundary.memory_size()
+    }
+}
+
+// Weights of a record, none for records of other objects
+fn collision_weight(rec: &HitRecord) -> Color {
+    rec.collision_weight
+        .clone()
+        .unwrap_or_else(|| Color::new(1.0, 1.0, 1.0))
+}
+
+// Phase function scaled by the tracking weights of one collision
+struct Weighted {
+    phase_function: HenyeyGreenstein,
+}
+
+impl Material for Weighted {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let (attenuation, scattered) = self.phase_function.scatter(r_in, rec)?;
+        Some((&attenuation * &collision_weight(rec), scattered))
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        &self.phase_function.eval(wo, wi, rec) * &collision_weight(rec)
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        self.phase_function.pdf(wo, wi, rec)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+}
+
+// Continues the ray unchanged, carrying the transmittance weights
+struct NullCollision;
+
+impl Material for NullCollision {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let weight = collision_weight(rec);
+        if weight.near_zero() {
+            return None;
+        }
+        let scattered = Ray::new(rec.p.clone(), r_in.direction().clone(), r_in.time());
+        Some((weight, scattered))
+    }
+}
@@ -1,14 +1,28 @@
-use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::material::{Material, MaterialIds};
 use crate::vec3::*;
 use crate::Ray;
 use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
     pub mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
+    // Index of the object inside the innermost list (or BVH) that produced this hit
+    pub object_id: Option<usize>,
+    // Number of the material, from the MaterialIds of the scene
+    pub material_id: Option<usize>,
+    // Shading tangent for anisotropic materials and fibers: along the fiber on
+    // curves, along the latitude on spheres
+    pub tangent: Option<Vec3>,
+    // Per channel weights of a collision in a heterogeneous medium, applied
+    // by the medium's materials
+    pub collision_weight: Option<Color>,
 }
 
 impl HitRecord {
@@ -17,6 +31,7 @@ impl HitRecord {
         p: Point3,
         outward_normal: &Vec3,
         t: f64,
+        (u, v): (f64, f64),
         mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
     ) -> HitRecord {
         let front_face = r.direction().dot(outward_normal) < 0.0;
@@ -30,11 +45,94 @@ impl HitRecord {
             normal,
             mat_ptr,
             t,
+            u,
+            v,
             front_face,
+            object_id: None,
+            material_id: None,
+            tangent: None,
+            collision_weight: None,
         }
     }
 }
 
 pub trait Hittable {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+
+    // Box enclosing the object over the time interval. None means the object is unbounded
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb>;
+
+    // Number of bounding box and primitive tests needed to resolve the ray.
+    // Only used by the traversal cost AOV, so it may re-run intersections.
+    fn traversal_cost(&self, _r: &Ray, _t_min: f64, _t_max: f64) -> usize {
+        1
+    }
+
+    // Uniformly distributed point on the surface, with the outward normal and
+    // front_face set. Needed for objects that are sampled as lights.
+    fn sample_surface(&self) -> Option<HitRecord> {
+        None
+    }
+
+    // The density of sample_surface is 1 / area
+    fn area(&self) -> f64 {
+        0.0
+    }
+
+    // Every surface crossing in [t_min, t_max], sorted by t. Closed objects
+    // alternate between entering (front_face) and leaving hits.
+    fn all_hits(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord> {
+        let mut hits: Vec<HitRecord> = Vec::new();
+        let mut t = t_min;
+        while let Some(rec) = self.hit(r, t, t_max) {
+            // Step past the hit, so that it is not found again
+            t = rec.t + 1e-9 * rec.t.abs().max(1.0);
+            hits.push(rec);
+        }
+        hits
+    }
+
+    // Numbers every material the object may hit with, see MaterialIds
+    fn number_materials(&self, _ids: &mut MaterialIds) {}
+
+    // Bytes owned by the object, for memory reports.
+    // Objects shared through an Arc are not counted by their users.
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+// Shared objects, such as the static part of an animated world
+impl<T: Hittable + ?Sized> Hittable for Arc<T> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.as_ref().hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.as_ref().bounding_box(time0, time1)
+    }
+
+    fn traversal_cost(&self, r: &Ray, t_min: f64, t_max: f64) -> usize {
+        self.as_ref().traversal_cost(r, t_min, t_max)
+    }
+
+    fn all_hits(&self, r: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord> {
+        self.as_ref().all_hits(r, t_min, t_max)
+    }
+
+    fn sample_surface(&self) -> Option<HitRecord> {
+        self.as_ref().sample_surface()
+    }
+
+    fn area(&self) -> f64 {
+        self.as_ref().area()
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        self.as_ref().number_materials(ids)
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
 }
@@ -1,4 +1,6 @@
+use crate::aabb::Aabb;
 use crate::hittable::*;
+use crate::material::MaterialIds;
 use crate::Ray;
 
 pub struct HittableList {
@@ -17,22 +19,68 @@ impl HittableList {
         self.objects.push(object);
         self
     }
+
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable + Sync + Send + 'static>> {
+        self.objects
+    }
 }
 
 impl Hittable for HittableList {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let (record, _) =
-            self.objects
-                .iter()
-                .fold((None, t_max), |(rec, closest_so_far), object| {
-                    if let Some(v) = object.hit(r, t_min, closest_so_far) {
-                        let t = v.t;
-                        (Some(v), t)
-                    } else {
-                        (rec, closest_so_far)
-                    }
-                });
+        let (record, _) = self.objects.iter().enumerate().fold(
+            (None, t_max),
+            |(rec, closest_so_far), (id, object)| {
+                if let Some(mut v) = object.hit(r, t_min, closest_so_far) {
+                    let t = v.t;
+                    v.object_id.get_or_insert(id);
+                    (Some(v), t)
+                } else {
+                    (rec, closest_so_far)
+                }
+            },
+        );
 
         record
     }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let (first, rest) = self.objects.split_first()?;
+        rest.iter()
+            .try_fold(first.bounding_box(time0, time1)?, |acc, object| {
+                Some(Aabb::surrounding_box(
+                    &acc,
+                    &object.bounding_box(time0, time1)?,
+                ))
+            })
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        self.objects
+            .iter()
+            .for_each(|object| object.number_materials(ids));
+    }
+
+    fn traversal_cost(&self, r: &Ray, t_min: f64, t_max: f64) -> usize {
+        let (cost, _) = self
+            .objects
+            .iter()
+            .fold((0, t_max), |(cost, closest_so_far), object| {
+                let cost = cost + object.traversal_cost(r, t_min, closest_so_far);
+                match object.hit(r, t_min, closest_so_far) {
+                    Some(v) => (cost, v.t),
+                    None => (cost, closest_so_far),
+                }
+            });
+        cost
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.objects.capacity() * std::mem::size_of::<Box<dyn Hittable + Sync + Send>>()
+            + self
+                .objects
+                .iter()
+                .map(|object| object.memory_size())
+                .sum::<usize>()
+    }
 }
@@ -0,0 +1,151 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::MaterialIds;
+use crate::transform::Transform;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::sync::Arc;
+
+// Shared object placed in the world by a transform. Rays are moved into object
+// space, so the object's own structure is reused by every instance.
+pub struct Instance {
+    object: Arc<dyn Hittable + Sync + Send + 'static>,
+    transform: Transform,
+}
+
+impl Instance {
+    pub fn new(object: Arc<dyn Hittable + Sync + Send + 'static>, transform: Transform) -> Self {
+        Instance { object, transform }
+    }
+
+    fn object_ray(&self, r: &Ray) -> Ray {
+        // The direction is not normalized, so t is the same in both spaces
+        Ray::new(
+            self.transform.inverse_point(r.origin()),
+            self.transform.inverse_vector(r.direction()),
+            r.time(),
+        )
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut rec = self.object.hit(&self.object_ray(r), t_min, t_max)?;
+        // The sign of normal . direction is preserved, so front_face stays valid
+        rec.p = r.at(rec.t);
+        rec.normal = self.transform.normal(&rec.normal);
+        rec.tangent = rec
+            .tangent
+            .map(|tangent| self.transform.vector(&tangent).normalize());
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let bbox = self.object.bounding_box(time0, time1)?;
+        let (min, max) = (&bbox.minimum, &bbox.maximum);
+        let corner = |i: usize| {
+            self.transform.point(&Point3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            ))
+        };
+        let first = corner(0);
+        Some((1..8).fold(Aabb::new(first.clone(), first), |acc, i| {
+            let c = corner(i);
+            Aabb::surrounding_box(&acc, &Aabb::new(c.clone(), c))
+        }))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        self.object.number_materials(ids);
+    }
+
+    fn traversal_cost(&self, r: &Ray, t_min: f64, t_max: f64) -> usize {
+        self.object
+            .traversal_cost(&self.object_ray(r), t_min, t_max)
+    }
+
+    // Only uniform densities under rotation, translation and uniform scaling,
+    // other transforms stretch the surface unevenly.
+    fn sample_surface(&self) -> Option<HitRecord> {
+        let mut rec = self.object.sample_surface()?;
+        rec.p = self.transform.point(&rec.p);
+        rec.normal = self.transform.normal(&rec.normal);
+        Some(rec)
+    }
+
+    // Exact under rotation, translation and uniform scaling. Other transforms
+    // stretch the surface unevenly, it is then scaled by their mean stretch.
+    fn area(&self) -> f64 {
+        let axis = |x, y, z| self.transform.vector(&Vec3::new(x, y, z));
+        let volume = axis(1.0, 0.0, 0.0)
+            .dot(&axis(0.0, 1.0, 0.0).cross(&axis(0.0, 0.0, 1.0)))
+            .abs();
+        self.object.area() * volume.powf(2.0 / 3.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lambertian::Lambertian;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::vec3::Color;
+    use std::f64::consts::PI;
+
+    fn unit_sphere() -> Arc<dyn Hittable + Sync + Send + 'static> {
+        let material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+            Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        Arc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, material))
+    }
+
+    // Unit sphere stretched into an ellipsoid with semi-axes 2, 1 and 0.5
+    fn ellipsoid() -> Instance {
+        let transform = Transform::identity()
+            .scale(Vec3::new(2.0, 1.0, 0.5))
+            .translate(Vec3::new(1.0, 2.0, 3.0));
+        Instance::new(unit_sphere(), transform)
+    }
+
+    #[test]
+    fn hits_under_non_uniform_scale() {
+        let ellipsoid = ellipsoid();
+        let r = Ray::new(Point3::new(-4.0, 2.0, 3.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let rec = ellipsoid.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.t - 3.0).abs() < 1e-9);
+        assert!((&rec.normal - &Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+
+        // Back along the normal of a point on the surface, which is the
+        // gradient of (x / 2)² + y² + (z / 0.5)²
+        let (theta, phi) = (1.1_f64, 0.7_f64);
+        let local = Vec3::new(
+            2.0 * theta.sin() * phi.cos(),
+            theta.sin() * phi.sin(),
+            0.5 * theta.cos(),
+        );
+        let normal = Vec3::new(local.x / 4.0, local.y, local.z / 0.25).normalize();
+        let p = &local + &Vec3::new(1.0, 2.0, 3.0);
+        let r = Ray::new(&p + &(2.0 * &normal), -2.5 * &normal, 0.0);
+        let rec = ellipsoid.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.t - 0.8).abs() < 1e-9);
+        assert!((&rec.p - &p).length() < 1e-9);
+        assert!((&rec.normal - &normal).length() < 1e-9);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn area_follows_the_scale() {
+        let transform = Transform::identity()
+            .scale(Vec3::new(3.0, 3.0, 3.0))
+            .rotate(Vec3::new(1.0, 2.0, 0.5), 40.0);
+        let area = Instance::new(unit_sphere(), transform).area();
+        assert!((area - 9.0 * 4.0 * PI).abs() < 1e-9);
+
+        // Between the stretch of the two smallest and of the two largest
+        // axes, about 1.26 times the unit sphere's for this ellipsoid
+        let area = ellipsoid().area() / (4.0 * PI);
+        assert!((0.5..=2.0).contains(&area), "{}", area);
+    }
+}
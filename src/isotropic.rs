@@ -0,0 +1,44 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::Color;
+use crate::Ray;
+use crate::Vec3;
+use std::f64::consts::PI;
+
+// Phase function that scatters uniformly in all directions
+pub struct Isotropic {
+    albedo: Color,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let scattered = Ray::new(
+            rec.p.clone(),
+            Vec3::random_unit_vector(&mut rand::thread_rng()),
+            r_in.time(),
+        );
+        Some((self.albedo.clone(), scattered))
+    }
+
+    fn eval(&self, _wo: &Vec3, _wi: &Vec3, _rec: &HitRecord) -> Color {
+        &self.albedo / (4.0 * PI)
+    }
+
+    fn pdf(&self, _wo: &Vec3, _wi: &Vec3, _rec: &HitRecord) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+}
@@ -1,21 +1,29 @@
 use crate::hittable::HitRecord;
 use crate::material::Material;
+use crate::texture::Texture;
 use crate::Color;
 use crate::Ray;
 use crate::Vec3;
+use std::f64::consts::PI;
 
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Box<dyn Texture + Sync + Send>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
-        Self { albedo }
+        Self::textured(albedo)
+    }
+
+    pub fn textured(albedo: impl Texture + Sync + Send + 'static) -> Self {
+        Self {
+            albedo: Box::new(albedo),
+        }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
         let mut scatter_direction = &rec.normal + Vec3::random_unit_vector(&mut rand::thread_rng());
 
         // Catch degenerate scatter direction
@@ -23,9 +31,25 @@ impl Material for Lambertian {
             scatter_direction = rec.normal.clone();
         }
 
-        let scattered = Ray::new(rec.p.clone(), scatter_direction);
-        let attenuation = self.albedo.clone();
+        let scattered = Ray::new(rec.p.clone(), scatter_direction, r_in.time());
+        let attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
 
         Some((attenuation, scattered))
     }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        if rec.normal.dot(wo) > 0. && rec.normal.dot(wi) > 0. {
+            self.albedo.value(rec.u, rec.v, &rec.p) / PI
+        } else {
+            Color::new(0., 0., 0.)
+        }
+    }
+
+    fn pdf(&self, _wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        rec.normal.dot(wi).max(0.) / PI
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
 }
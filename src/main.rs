@@ -1,117 +1,155 @@
 mod vec3;
-use vec3::{Color, Point3, Vec3};
+use vec3::{Color, Vec3};
 
 mod color;
 mod ray;
 use ray::Ray;
 
+mod aabb;
 mod hittable;
 use hittable::Hittable;
+mod animation;
+mod box_shape;
+mod bvh;
+mod cone;
+mod csg;
+mod curve;
+mod cylinder;
+mod disk;
+mod heightfield;
+mod instance;
+mod metaballs;
+mod moving_sphere;
+mod plane;
+mod quad;
+mod roots;
+mod sdf;
+mod sdf_shape;
 mod sphere;
-use sphere::Sphere;
+mod tlas;
+mod torus;
+mod transform;
 
 mod hittable_list;
-use hittable_list::HittableList;
 
 mod camera;
-use camera::Camera;
 
 mod material;
-use material::Material;
 
 mod lambertian;
-use lambertian::Lambertian;
+
+mod hair;
+
+mod oren_nayar;
 
 mod metal;
-use metal::Metal;
+
+mod conductor;
+
+mod microfacet;
+
+mod coated;
+
+mod mix_material;
 
 mod dielectrics;
-use dielectrics::Dielectrics;
+
+mod rough_dielectric;
+
+mod thin_film;
+
+mod principled;
+
+mod diffuse_light;
+
+mod isotropic;
+
+mod constant_medium;
+
+mod onb;
+
+mod perlin;
+
+mod density_field;
+
+mod henyey_greenstein;
+
+mod heterogeneous_medium;
+
+mod subsurface;
+
+mod texture;
+
+mod spectrum;
+use spectrum::Wavelengths;
 
 mod util;
 
-use std::io::{stderr, Write};
+mod aov;
+use aov::Aov;
+
+mod options;
+use options::{Integrator, Options};
+
+mod scene;
+use scene::Scene;
+
+mod film;
+use film::Film;
+
+mod bdpt;
+
+mod photon_map;
+use photon_map::PhotonMap;
+
+use std::fs::File;
+use std::io::{stderr, stdout, BufWriter, Write};
 
 use rand::Rng;
 use rayon::prelude::*;
 use std::sync::Arc;
 
-fn ray_color(r: &Ray, world: &impl Hittable, rng: &mut impl rand::Rng, depth: u64) -> Color {
+fn ray_color(r: &Ray, scene: &Scene, depth: u64) -> Color {
     if depth == 0 {
         return Color::new(0., 0., 0.);
     }
-    if let Some(rec) = world.hit(r, 0.001, f64::INFINITY) {
+    if let Some(rec) = scene.world.hit(r, 0.001, f64::INFINITY) {
         // let target = &(rec.p) + &(rec.normal).random_in_hemisphere(rng);
         // 0.5 * ray_color(&Ray::new(rec.p.clone(), target - rec.p), world, rng, depth - 1)
+        let emitted = rec.mat_ptr.emitted(&rec);
         if let Some((attenuation, scattered)) = rec.mat_ptr.scatter(r, &rec) {
-            attenuation * ray_color(&scattered, world, rng, depth - 1)
+            emitted + attenuation * ray_color(&scattered, scene, depth - 1)
         } else {
-            Color::new(0., 0., 0.)
+            emitted
         }
     } else {
-        let unit_direction = r.direction().clone().normalize();
-        let t = 0.5 * (unit_direction.y + 1.0);
-        (1. - t) * Color::new(1., 1., 1.) + t * Color::new(0.5, 0.7, 1.)
+        scene.background.color(r)
     }
 }
 
-fn random_scene() -> HittableList {
-    let world = HittableList::new();
-
-    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
-        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
-
-    let world = world.push(Sphere::new(
-        Point3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        ground_material,
-    ));
-
-    let world = (-11..11).zip(-11..11).fold(world, |world, (a, b)| {
-        let mut rng = rand::thread_rng();
-        let choose_mat = rng.gen_range(0.0..1.0);
-        let center = Point3::new(
-            a as f64 + 0.9 * rng.gen_range(0.0..1.0),
-            0.2,
-            b as f64 + 0.9 * rng.gen_range(0.0..1.0),
-        );
-
-        if (&center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
-            let sphere_material: Box<dyn Material + Sync + Send + 'static> = if choose_mat < 0.8 {
-                // diffuse
-                let albedo = Color::vec3_random_range(&mut rng, 0.0..1.0);
-                Box::new(Lambertian::new(albedo))
-            } else if choose_mat < 0.95 {
-                // metal
-                let albedo = Color::vec3_random_range(&mut rng, 0.0..0.5);
-                let fuzz = rng.gen_range(0.0..0.5);
-                Box::new(Metal::new(albedo, fuzz))
-            } else {
-                // glass
-                Box::new(Dielectrics::new(1.0))
-            };
-            world.push(Sphere::new(center, 0.2, Arc::new(sphere_material)))
+// ray_color at the wavelengths carried by the path, one per channel
+fn ray_color_spectral(r: &Ray, scene: &Scene, depth: u64, wavelengths: &mut Wavelengths) -> Color {
+    if depth == 0 {
+        return Color::new(0., 0., 0.);
+    }
+    if let Some(rec) = scene.world.hit(r, 0.001, f64::INFINITY) {
+        let emitted = wavelengths.uplift(&rec.mat_ptr.emitted(&rec));
+        if let Some((attenuation, scattered)) = rec.mat_ptr.scatter_spectral(r, &rec, wavelengths) {
+            emitted + attenuation * ray_color_spectral(&scattered, scene, depth - 1, wavelengths)
         } else {
-            world
+            emitted
         }
-    });
-
-    let material1: Arc<Box<dyn Material + Sync + Send + 'static>> =
-        Arc::new(Box::new(Dielectrics::new(1.5)));
-
-    let material2: Arc<Box<dyn Material + Sync + Send + 'static>> =
-        Arc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))));
-
-    let material3: Arc<Box<dyn Material + Sync + Send + 'static>> =
-        Arc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
-
-    world
-        .push(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1))
-        .push(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2))
-        .push(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3))
+    } else {
+        wavelengths.uplift(&scene.background.color(r))
+    }
 }
 
 fn main() {
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+
     // Image
 
     let aspect_ratio = 3.0 / 2.0;
@@ -120,65 +158,203 @@ fn main() {
     let sample_per_pixel = 500;
     let max_depth = 50;
 
-    // World
-
-    let world = Arc::new(random_scene());
-
-    // Camera
-
-    let lookfrom = Point3::new(13.0, 2.0, 3.0);
-    let lookat = Point3::new(0.0, 0.0, 0.0);
-    let viewup = Point3::new(0.0, 1.0, 0.0);
-    let dist_to_focus = 10.0;
-    let aperture = 0.1;
+    // The main image comes first, extra layers share its camera samples
+    let outputs: Vec<Aov> = std::iter::once(options.aov)
+        .chain(options.layers.iter().copied())
+        .collect();
 
-    let cam = Camera::new(
-        lookfrom,
-        lookat,
-        viewup,
-        20.0,
-        aspect_ratio,
-        aperture,
-        dist_to_focus,
-    );
+    match options.frames.clone() {
+        None => {
+            // World
+
+            let scene = scene::load(&options.scene, aspect_ratio).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            });
+            if options.memory_report {
+                match &scene.memory_report {
+                    Some(report) => eprintln!("{}", report),
+                    None => eprintln!("No instances in scene '{}'", options.scene),
+                }
+            }
+
+            // Render
+
+            let layers = render(
+                Arc::new(scene),
+                &options,
+                &outputs,
+                (image_width, image_height),
+                sample_per_pixel,
+                max_depth,
+            );
+            write_layers(&outputs, &layers, (image_width, image_height), None);
+        }
+        Some(frames) => {
+            let animation = scene::load_animation(&options.scene).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            });
+
+            for frame in frames {
+                eprintln!("\nFrame {}", frame);
+                let scene = animation.frame(frame as f64 / options.fps, aspect_ratio);
+                let layers = render(
+                    Arc::new(scene),
+                    &options,
+                    &outputs,
+                    (image_width, image_height),
+                    sample_per_pixel,
+                    max_depth,
+                );
+                write_layers(&outputs, &layers, (image_width, image_height), Some(frame));
+            }
+        }
+    }
 
-    let cam = Arc::new(cam);
+    eprintln!("\nDone.");
+}
 
-    // Render
+// One finished image per output
+fn render(
+    scene: Arc<Scene>,
+    options: &Options,
+    outputs: &[Aov],
+    (image_width, image_height): (usize, usize),
+    sample_per_pixel: usize,
+    max_depth: u64,
+) -> Vec<Vec<Color>> {
+    let film = Arc::new(Film::new(image_width, image_height));
+    let photon_map = Arc::new(match options.integrator {
+        Integrator::Photon => Some(PhotonMap::new(
+            &scene,
+            options.photons,
+            max_depth as usize,
+            options.gather,
+            options.gather_radius,
+        )),
+        _ => None,
+    });
 
-    println!("P3\n{} {}\n255", image_width, image_height);
+    let integrator = options.integrator;
+    let spectral = options.spectral;
+    let render_film = film.clone();
+    let outputs_per_pixel = Arc::new(outputs.to_vec());
 
-    let pixel_colors: Vec<Color> = (0..image_height)
+    let pixel_colors: Vec<Vec<Color>> = (0..image_height)
         .into_par_iter()
         .rev()
         .map(move |j| {
             eprint!("\rScanlines remaining: {} ", j);
             stderr().flush().unwrap();
 
-            let world = world.clone();
-            let cam = cam.clone();
+            let scene = scene.clone();
+            let film = render_film.clone();
+            let photon_map = photon_map.clone();
+            let outputs = outputs_per_pixel.clone();
 
             (0..image_width).into_par_iter().map(move |i| {
                 let mut rng = rand::thread_rng();
 
-                let world = world.clone();
-                let cam = cam.clone();
-
-                (0..sample_per_pixel).fold(Color::new(0., 0., 0.), |acc, _| {
-                    let u = (i as f64 + rng.gen::<f64>()) / (image_width as f64 - 1.);
-                    let v = (j as f64 + rng.gen::<f64>()) / (image_height as f64 - 1.);
-
-                    let r = cam.get_ray(u, v);
-                    acc + ray_color(&r, world.as_ref(), &mut rng, max_depth)
-                }) / sample_per_pixel as f64
+                let scene = scene.clone();
+                let film = film.clone();
+                let photon_map = photon_map.clone();
+
+                let init = vec![Color::new(0., 0., 0.); outputs.len()];
+                (0..sample_per_pixel)
+                    .fold(init, |mut acc, _| {
+                        let u = (i as f64 + rng.gen::<f64>()) / image_width as f64;
+                        let v = (j as f64 + rng.gen::<f64>()) / image_height as f64;
+
+                        let r = scene.camera.get_ray(u, v);
+                        acc.iter_mut().zip(outputs.iter()).for_each(|(acc, aov)| {
+                            *acc += &match (aov, integrator) {
+                                (Aov::Beauty, Integrator::Path) if spectral => {
+                                    let mut wavelengths = Wavelengths::sample(rng.gen());
+                                    let values =
+                                        ray_color_spectral(&r, &scene, max_depth, &mut wavelengths);
+                                    wavelengths.to_rgb(&values)
+                                }
+                                (Aov::Beauty, Integrator::Path) => ray_color(&r, &scene, max_depth),
+                                (Aov::Beauty, Integrator::Bdpt) => {
+                                    bdpt::bdpt_color(&r, &scene, &film, max_depth as usize)
+                                }
+                                (Aov::Beauty, Integrator::Photon) => photon_map::photon_color(
+                                    &r,
+                                    &scene,
+                                    photon_map.as_ref().as_ref().unwrap(),
+                                    max_depth,
+                                ),
+                                (aov, _) => aov.sample(&r, &scene),
+                            };
+                        });
+                        acc
+                    })
+                    .into_iter()
+                    .map(|c| c / sample_per_pixel as f64)
+                    .collect()
             })
         })
         .flatten()
         .collect();
 
-    pixel_colors.iter().for_each(|pixel_color| {
-        println!("{}", pixel_color);
-    });
+    // Light tracing contributions, one light path was traced per camera sample
+    let splats = film.splats(1.0 / sample_per_pixel as f64);
+
+    outputs
+        .iter()
+        .enumerate()
+        .map(|(k, aov)| {
+            let mut layer: Vec<Color> = pixel_colors.iter().map(|p| p[k].clone()).collect();
+            if *aov == Aov::Beauty {
+                layer
+                    .iter_mut()
+                    .zip(splats.iter())
+                    .for_each(|(c, splat)| *c += splat);
+            }
+            aov.finish(&mut layer);
+            layer
+        })
+        .collect()
+}
 
-    eprintln!("\nDone.");
+// A single image goes to stdout and its extra layers to <name>.ppm,
+// frames of an animation all go to <name>_<frame>.ppm
+fn write_layers(
+    outputs: &[Aov],
+    layers: &[Vec<Color>],
+    (image_width, image_height): (usize, usize),
+    frame: Option<usize>,
+) {
+    for (k, (aov, layer)) in outputs.iter().zip(layers.iter()).enumerate() {
+        let result = match frame {
+            None if k == 0 => {
+                let mut out = BufWriter::new(stdout());
+                color::write_ppm(
+                    &mut out,
+                    image_width,
+                    image_height,
+                    layer,
+                    aov.gamma_correct(),
+                )
+            }
+            _ => {
+                let path = match frame {
+                    Some(frame) => format!("{}_{:04}.ppm", aov.name(), frame),
+                    None => format!("{}.ppm", aov.name()),
+                };
+                File::create(&path).and_then(|file| {
+                    let mut out = BufWriter::new(file);
+                    color::write_ppm(
+                        &mut out,
+                        image_width,
+                        image_height,
+                        layer,
+                        aov.gamma_correct(),
+                    )
+                })
+            }
+        };
+        result.expect("failed to write image");
+    }
 }
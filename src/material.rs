@@ -1,15 +1,75 @@
 use crate::hittable::HitRecord;
+use crate::spectrum::Wavelengths;
 use crate::Color;
 use crate::Ray;
+use crate::Vec3;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 pub trait Material {
     // dynamic objectで管理する関係上、&mut implを受け取れないので、rand関係を利用する場合は、method内で作成すること
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)>;
+
+    // Spectral rendering: the attenuation at the wavelengths carried by the
+    // path, one per channel. Materials that disperse light override this.
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: &mut Wavelengths,
+    ) -> Option<(Color, Ray)> {
+        let (attenuation, scattered) = self.scatter(r_in, rec)?;
+        Some((wavelengths.uplift(&attenuation), scattered))
+    }
+
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::new(0., 0., 0.)
+    }
+
+    // Below are only used by integrators that connect paths (bdpt).
+    // wo and wi are unit vectors pointing away from rec.p, wo towards where the path came from.
+
+    // BSDF value, without the cosine term
+    fn eval(&self, _wo: &Vec3, _wi: &Vec3, _rec: &HitRecord) -> Color {
+        Color::new(0., 0., 0.)
+    }
+
+    // Solid angle density with which `scatter` picks wi when leaving along wo
+    fn pdf(&self, _wo: &Vec3, _wi: &Vec3, _rec: &HitRecord) -> f64 {
+        0.0
+    }
+
+    // Materials without eval/pdf are treated as delta distributions that can only be sampled
+    fn is_specular(&self) -> bool {
+        true
+    }
+
+    // Phase functions of media scatter at a point rather than off a surface,
+    // so there is no cosine term at their vertices
+    fn is_volumetric(&self) -> bool {
+        false
+    }
+}
+
+// Numbers for the materials of a scene, in the order they are first found
+// when it is built, so that they are the same from run to run
+#[derive(Default)]
+pub struct MaterialIds {
+    ids: HashMap<usize, usize>,
 }
 
-pub struct UninitMaterial {}
-impl Material for UninitMaterial {
-    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<(Color, Ray)> {
-        None
+impl MaterialIds {
+    fn key(material: &Arc<Box<dyn Material + Sync + Send + 'static>>) -> usize {
+        Arc::as_ptr(material) as usize
+    }
+
+    // The number of the material, given the next one if it is new
+    pub fn id(&mut self, material: &Arc<Box<dyn Material + Sync + Send + 'static>>) -> usize {
+        let next = self.ids.len();
+        *self.ids.entry(Self::key(material)).or_insert(next)
+    }
+
+    pub fn get(&self, material: &Arc<Box<dyn Material + Sync + Send + 'static>>) -> Option<usize> {
+        self.ids.get(&Self::key(material)).copied()
     }
 }
@@ -0,0 +1,289 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::sphere::get_sphere_uv;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::sync::Arc;
+
+// Subdivisions of the ray segment before giving up on separating roots
+const MAX_DEPTH: usize = 32;
+const NEWTON_STEPS: usize = 16;
+
+// Field contribution strength * (1 - d²/radius²)³, zero beyond radius.
+// A negative strength carves into the other blobs.
+struct Blob {
+    center: Point3,
+    radius: f64,
+    strength: f64,
+}
+
+impl Blob {
+    // The field vanishes outside of it
+    fn bounds(&self) -> Aabb {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::new(&self.center - &r, &self.center + &r)
+    }
+
+    fn field(&self, p: &Point3) -> f64 {
+        let falloff = (1.0 - (p - &self.center).norm() / self.radius.powi(2)).max(0.0);
+        self.strength * falloff.powi(3)
+    }
+
+    fn gradient(&self, p: &Point3) -> Vec3 {
+        let offset = p - &self.center;
+        let r2 = self.radius.powi(2);
+        let falloff = (1.0 - offset.norm() / r2).max(0.0);
+        (-6.0 * self.strength * falloff.powi(2) / r2) * offset
+    }
+}
+
+// A blob seen along a ray: its squared distance is a t² + 2 b t + c, and the
+// field is zero outside of [t0, t1]
+struct RayBlob<'a> {
+    blob: &'a Blob,
+    a: f64,
+    b: f64,
+    c: f64,
+    t0: f64,
+    t1: f64,
+}
+
+impl RayBlob<'_> {
+    // Bounds of the contribution and of its t derivative over [ta, tb]
+    fn range(&self, ta: f64, tb: f64) -> Option<((f64, f64), (f64, f64))> {
+        if tb < self.t0 || self.t1 < ta {
+            return None;
+        }
+        let d2 = |t: f64| (self.a * t + 2.0 * self.b) * t + self.c;
+        let vertex = (-self.b / self.a).clamp(ta, tb);
+        let (d2_min, d2_max) = (d2(vertex), d2(ta).max(d2(tb)));
+
+        // The falloff decreases with the distance
+        let r2 = self.blob.radius.powi(2);
+        let falloff = |d2: f64| (1.0 - d2 / r2).max(0.0);
+        let (f_min, f_max) = (falloff(d2_max), falloff(d2_min));
+        let s = self.blob.strength;
+        let field = ordered(s * f_min.powi(3), s * f_max.powi(3));
+
+        // d/dt = -3 s falloff² (2 a t + 2 b) / r², a product of two intervals
+        let squared = (f_min.powi(2), f_max.powi(2));
+        let slope = (2.0 * (self.a * ta + self.b), 2.0 * (self.a * tb + self.b));
+        let k = -3.0 * s / r2;
+        let corners = [
+            k * squared.0 * slope.0,
+            k * squared.0 * slope.1,
+            k * squared.1 * slope.0,
+            k * squared.1 * slope.1,
+        ];
+        let derivative = corners
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &c| {
+                (lo.min(c), hi.max(c))
+            });
+        Some((field, derivative))
+    }
+}
+
+fn ordered(a: f64, b: f64) -> (f64, f64) {
+    (a.min(b), a.max(b))
+}
+
+// Implicit surface where the summed field of the blobs reaches threshold.
+// Roots along a ray are isolated by interval bisection and polished by Newton
+// steps; normals come from the analytic gradient.
+pub struct Metaballs {
+    blobs: Vec<Blob>,
+    threshold: f64,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Metaballs {
+    pub fn new(threshold: f64, mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>) -> Self {
+        Metaballs {
+            blobs: Vec::new(),
+            threshold,
+            mat_ptr,
+        }
+    }
+
+    pub fn blob(mut self, center: Point3, radius: f64, strength: f64) -> Self {
+        self.blobs.push(Blob {
+            center,
+            radius,
+            strength,
+        });
+        self
+    }
+
+    // Positive inside the surface
+    fn field(&self, blobs: &[RayBlob], p: &Point3) -> f64 {
+        blobs.iter().map(|b| b.blob.field(p)).sum::<f64>() - self.threshold
+    }
+
+    fn gradient(&self, blobs: &[RayBlob], p: &Point3) -> Vec3 {
+        blobs
+            .iter()
+            .fold(Vec3::new(0.0, 0.0, 0.0), |g, b| g + b.blob.gradient(p))
+    }
+
+    // First crossing of the surface in [ta, tb]
+    fn find_root(&self, r: &Ray, blobs: &[RayBlob], ta: f64, tb: f64, depth: usize) -> Option<f64> {
+        let ((f_lo, f_hi), (d_lo, d_hi)) = blobs.iter().filter_map(|b| b.range(ta, tb)).fold(
+            ((-self.threshold, -self.threshold), (0.0, 0.0)),
+            |((f_lo, f_hi), (d_lo, d_hi)), ((f0, f1), (d0, d1))| {
+                ((f_lo + f0, f_hi + f1), (d_lo + d0, d_hi + d1))
+            },
+        );
+        if f_lo > 0.0 || f_hi < 0.0 {
+            return None;
+        }
+
+        // A monotonic field crosses at most once
+        let monotonic = d_lo > 0.0 || d_hi < 0.0;
+        if monotonic || depth == MAX_DEPTH {
+            let fa = self.field(blobs, &r.at(ta));
+            let fb = self.field(blobs, &r.at(tb));
+            if (fa > 0.0) == (fb > 0.0) {
+                return None;
+            }
+            return Some(self.newton(r, blobs, (ta, fa), tb));
+        }
+
+        let mid = 0.5 * (ta + tb);
+        self.find_root(r, blobs, ta, mid, depth + 1)
+            .or_else(|| self.find_root(r, blobs, mid, tb, depth + 1))
+    }
+
+    // Newton iteration kept inside a shrinking bracket, bisecting when it leaves it
+    fn newton(&self, r: &Ray, blobs: &[RayBlob], (ta, fa): (f64, f64), tb: f64) -> f64 {
+        let (mut lo, mut hi) = (ta, tb);
+        let mut t = 0.5 * (lo + hi);
+        for _ in 0..NEWTON_STEPS {
+            let p = r.at(t);
+            let f = self.field(blobs, &p);
+            if f == 0.0 {
+                break;
+            }
+            if (f > 0.0) == (fa > 0.0) {
+                lo = t;
+            } else {
+                hi = t;
+            }
+            let df = self.gradient(blobs, &p).dot(r.direction());
+            let next = t - f / df;
+            // Converged onto the end of the bracket that t just became
+            if (next - t).abs() <= 1e-12 * t.abs().max(1.0) {
+                t = next;
+                break;
+            }
+            t = if lo < next && next < hi {
+                next
+            } else {
+                0.5 * (lo + hi)
+            };
+        }
+        t
+    }
+}
+
+impl Hittable for Metaballs {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let a = r.direction().norm();
+        let blobs: Vec<RayBlob> = self
+            .blobs
+            .iter()
+            .filter_map(|blob| {
+                let (t0, t1) = blob.bounds().interval(r, t_min, t_max)?;
+                let oc = r.origin() - &blob.center;
+                Some(RayBlob {
+                    blob,
+                    a,
+                    b: oc.dot(r.direction()),
+                    c: oc.norm(),
+                    t0,
+                    t1,
+                })
+            })
+            .collect();
+        if blobs.is_empty() {
+            return None;
+        }
+        let ta = blobs.iter().map(|b| b.t0).fold(f64::INFINITY, f64::min);
+        let tb = blobs.iter().map(|b| b.t1).fold(f64::NEG_INFINITY, f64::max);
+
+        let t = self.find_root(r, &blobs, ta, tb, 0)?;
+        let p = r.at(t);
+        let outward_normal = -self.gradient(&blobs, &p).normalize();
+        let uv = get_sphere_uv(&outward_normal);
+        Some(HitRecord::from_face_normal(
+            r,
+            p,
+            &outward_normal,
+            t,
+            uv,
+            self.mat_ptr.clone(),
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        self.blobs
+            .iter()
+            .map(Blob::bounds)
+            .reduce(|a, b| Aabb::surrounding_box(&a, &b))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lambertian::Lambertian;
+    use crate::sphere::Sphere;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn single_blob_matches_the_analytic_sphere() {
+        let material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+            Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        // (1 - d² / 4)³ = 1 / 8 at d² = 2
+        let center = Point3::new(1.0, 0.5, -2.0);
+        let metaballs = Metaballs::new(0.125, material.clone()).blob(center.clone(), 2.0, 1.0);
+        let radius = 2.0_f64.sqrt();
+        let sphere = Sphere::new(center.clone(), radius, material);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        for i in 0..1000 {
+            // Half of the rays start inside the surface
+            let from = if i % 2 == 0 {
+                &center + &(5.0 * &Vec3::random_unit_vector(&mut rng))
+            } else {
+                &center + &(rng.gen_range(0.0..1.3) * &Vec3::random_unit_vector(&mut rng))
+            };
+            let target = &center + &(rng.gen_range(0.0..2.0) * &Vec3::random_unit_vector(&mut rng));
+            let r = Ray::new(from.clone(), target - from, 0.0);
+            match (
+                sphere.hit(&r, 0.001, f64::INFINITY),
+                metaballs.hit(&r, 0.001, f64::INFINITY),
+            ) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    assert!((a.t - b.t).abs() < 1e-9, "{} != {}", a.t, b.t);
+                    assert!((&a.normal - &b.normal).length() < 1e-9);
+                    assert_eq!(a.front_face, b.front_face);
+                }
+                // Only rays grazing the sphere may tell a touch from a miss
+                (Some(a), None) | (None, Some(a)) => {
+                    let distance = r.direction().cross(&(&center - r.origin())).length()
+                        / r.direction().length();
+                    assert!((distance - radius).abs() < 1e-6, "missed at {:?}", a.p);
+                }
+            }
+        }
+    }
+}
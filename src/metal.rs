@@ -29,6 +29,7 @@ impl Material for Metal {
         let scattered = Ray::new(
             rec.p.clone(),
             reflected + self.fuzz * Vec3::random_in_unit_sphere(&mut rng),
+            r_in.time(),
         );
         let attenuation = self.albedo.clone();
         if scattered.direction().dot(&rec.normal) > 0. {
@@ -0,0 +1,188 @@
+// GGX (Trowbridge-Reitz) microfacet distribution and Fresnel terms. Directions
+// are in a local shading frame with the normal along +z, see Onb::to_local.
+
+use crate::vec3::{Color, Vec3};
+use std::f64::consts::PI;
+
+// Below this the distribution is too close to a delta for floating point
+const MIN_ALPHA: f64 = 1e-3;
+
+pub struct Ggx {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl Ggx {
+    // Perceptual roughness in [0, 1], squared into the width of the distribution
+    pub fn new(roughness: f64) -> Self {
+        Ggx::anisotropic(roughness, 0.0)
+    }
+
+    // Stretched along the tangent by anisotropic in [0, 1], as in Disney's BRDF
+    pub fn anisotropic(roughness: f64, anisotropic: f64) -> Self {
+        let alpha = roughness.clamp(0.0, 1.0).powi(2);
+        let aspect = (1.0 - 0.9 * anisotropic.clamp(0.0, 1.0)).sqrt();
+        Ggx {
+            alpha_x: (alpha / aspect).max(MIN_ALPHA),
+            alpha_y: (alpha * aspect).max(MIN_ALPHA),
+        }
+    }
+
+    // Density of microfacet normals, per unit of projected area
+    pub fn d(&self, h: &Vec3) -> f64 {
+        if h.z <= 0.0 {
+            return 0.0;
+        }
+        let e = (h.x / self.alpha_x).powi(2) + (h.y / self.alpha_y).powi(2) + h.z * h.z;
+        1.0 / (PI * self.alpha_x * self.alpha_y * e * e)
+    }
+
+    fn lambda(&self, w: &Vec3) -> f64 {
+        if w.z == 0.0 {
+            return f64::INFINITY;
+        }
+        let tan2 = ((self.alpha_x * w.x).powi(2) + (self.alpha_y * w.y).powi(2)) / (w.z * w.z);
+        0.5 * (-1.0 + (1.0 + tan2).sqrt())
+    }
+
+    // Fraction of microfacets visible from w
+    pub fn g1(&self, w: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    // Fraction visible from both directions, height correlated
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    // Microfacet normal seen from wo, which must be above the surface.
+    // Heitz 2018, "Sampling the GGX Distribution of Visible Normals".
+    pub fn sample_visible(&self, wo: &Vec3, u1: f64, u2: f64) -> Vec3 {
+        let vh = Vec3::new(self.alpha_x * wo.x, self.alpha_y * wo.y, wo.z).normalize();
+        let len2 = vh.x * vh.x + vh.y * vh.y;
+        let t1 = if len2 > 0.0 {
+            Vec3::new(-vh.y, vh.x, 0.0) / len2.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = vh.cross(&t1);
+
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = p1 * &t1 + p2 * &t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * &vh;
+
+        Vec3::new(self.alpha_x * nh.x, self.alpha_y * nh.y, nh.z.max(1e-6)).normalize()
+    }
+
+    // Density of sample_visible
+    pub fn pdf_visible(&self, wo: &Vec3, h: &Vec3) -> f64 {
+        if wo.z <= 0.0 {
+            return 0.0;
+        }
+        self.g1(wo) * wo.dot(h).max(0.0) * self.d(h) / wo.z
+    }
+}
+
+// Microfacet normal that turns wo into wi, on the side of wo, which must have
+// wo.z > 0. eta is the index beyond the surface over the one in front. None
+// when the directions do not fit a single microfacet.
+pub fn half_vector(wo: &Vec3, wi: &Vec3, eta: f64) -> Option<Vec3> {
+    let reflection = wi.z > 0.0;
+    let h = if reflection {
+        wo + wi
+    } else {
+        -(wo + &(eta * wi))
+    };
+    if h.norm() == 0.0 {
+        return None;
+    }
+    let h = h.normalize();
+    let h = if h.z < 0.0 { -h } else { h };
+    // Both on the same side of the microfacet for reflection, on
+    // opposite sides for transmission
+    let facing = wo.dot(&h) > 0.0 && (wi.dot(&h) > 0.0) == reflection;
+    facing.then_some(h)
+}
+
+// Unpolarized reflectance of a conductor with complex index eta + i k
+pub fn fresnel_conductor(cos_theta_i: f64, eta: &Color, k: &Color) -> Color {
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_theta_i.clamp(0.0, 1.0).powi(2);
+        let sin2 = 1.0 - cos2;
+        let t0 = eta * eta - k * k - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+        let t2 = 2.0 * cos_theta_i * a;
+        let rs = (t1 - t2) / (t1 + t2);
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+        0.5 * (rp + rs)
+    };
+    Color::new(
+        channel(eta.x, k.x),
+        channel(eta.y, k.y),
+        channel(eta.z, k.z),
+    )
+}
+
+// Exact reflectance of a dielectric interface for unpolarized light, with
+// eta the index beyond the interface over the one in front. A negative cosine
+// arrives from beyond it.
+pub fn fresnel_dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let cos_theta_i = cos_theta_i.clamp(-1.0, 1.0);
+    let (eta, cos_theta_i) = if cos_theta_i < 0.0 {
+        (1.0 / eta, -cos_theta_i)
+    } else {
+        (eta, cos_theta_i)
+    };
+    let sin_theta_t = (1.0 - cos_theta_i * cos_theta_i).sqrt() / eta;
+    if sin_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = (1.0 - sin_theta_t * sin_theta_t).sqrt();
+    let parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    0.5 * (parallel * parallel + perpendicular * perpendicular)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dielectric_fresnel() {
+        // ((n - 1) / (n + 1))^2 at normal incidence
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-12);
+        assert!((fresnel_dielectric(-1.0, 1.5) - 0.04).abs() < 1e-12);
+        assert!((fresnel_dielectric(0.0, 1.5) - 1.0).abs() < 1e-12);
+        // Total internal reflection past the critical angle from inside
+        assert_eq!(fresnel_dielectric(-0.5, 1.5), 1.0);
+        assert!(fresnel_dielectric(-0.9, 1.5) < 1.0);
+        // No interface
+        assert!(fresnel_dielectric(0.3, 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn conductor_fresnel() {
+        let eta = Color::new(0.2, 0.9, 1.1);
+        let k = Color::new(3.9, 2.5, 2.4);
+        let normal = fresnel_conductor(1.0, &eta, &k);
+        for i in 0..3 {
+            let expected =
+                ((eta[i] - 1.0).powi(2) + k[i] * k[i]) / ((eta[i] + 1.0).powi(2) + k[i] * k[i]);
+            assert!((normal[i] - expected).abs() < 1e-12);
+        }
+        let grazing = fresnel_conductor(0.0, &eta, &k);
+        for i in 0..3 {
+            assert!((grazing[i] - 1.0).abs() < 1e-12);
+        }
+        // A dielectric seen as a conductor without absorption
+        let glass = fresnel_conductor(0.7, &Color::new(1.5, 1.5, 1.5), &Color::new(0.0, 0.0, 0.0));
+        assert!((glass.x - fresnel_dielectric(0.7, 1.5)).abs() < 1e-12);
+    }
+}
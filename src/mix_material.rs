@@ -0,0 +1,91 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::spectrum::Wavelengths;
+use crate::texture::Texture;
+use crate::Color;
+use crate::Ray;
+use crate::Vec3;
+use rand::Rng;
+use std::sync::Arc;
+
+// Blend of two materials, weighted towards the second by a mask read from the
+// red channel of a texture. scatter picks one of them at random by the
+// weight, so each keeps its own attenuation.
+pub struct MixMaterial {
+    first: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    second: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    mask: Box<dyn Texture + Sync + Send>,
+}
+
+impl MixMaterial {
+    pub fn new(
+        first: Arc<Box<dyn Material + Sync + Send + 'static>>,
+        second: Arc<Box<dyn Material + Sync + Send + 'static>>,
+        weight: f64,
+    ) -> Self {
+        Self::masked(first, second, Color::new(weight, weight, weight))
+    }
+
+    pub fn masked(
+        first: Arc<Box<dyn Material + Sync + Send + 'static>>,
+        second: Arc<Box<dyn Material + Sync + Send + 'static>>,
+        mask: impl Texture + Sync + Send + 'static,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            mask: Box::new(mask),
+        }
+    }
+
+    fn weight(&self, rec: &HitRecord) -> f64 {
+        self.mask.value(rec.u, rec.v, &rec.p).x.clamp(0.0, 1.0)
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        if rand::thread_rng().gen::<f64>() < self.weight(rec) {
+            self.second.scatter(r_in, rec)
+        } else {
+            self.first.scatter(r_in, rec)
+        }
+    }
+
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: &mut Wavelengths,
+    ) -> Option<(Color, Ray)> {
+        if rand::thread_rng().gen::<f64>() < self.weight(rec) {
+            self.second.scatter_spectral(r_in, rec, wavelengths)
+        } else {
+            self.first.scatter_spectral(r_in, rec, wavelengths)
+        }
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        let w = self.weight(rec);
+        (1.0 - w) * &self.first.emitted(rec) + w * &self.second.emitted(rec)
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        let w = self.weight(rec);
+        (1.0 - w) * &self.first.eval(wo, wi, rec) + w * &self.second.eval(wo, wi, rec)
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        let w = self.weight(rec);
+        (1.0 - w) * self.first.pdf(wo, wi, rec) + w * self.second.pdf(wo, wi, rec)
+    }
+
+    // A delta lobe in either can not be evaluated
+    fn is_specular(&self) -> bool {
+        self.first.is_specular() || self.second.is_specular()
+    }
+
+    fn is_volumetric(&self) -> bool {
+        self.first.is_volumetric() && self.second.is_volumetric()
+    }
+}
@@ -0,0 +1,92 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::sphere::get_sphere_uv;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::sync::Arc;
+
+// Sphere whose center moves linearly from center0 at time0 to center1 at time1
+pub struct MovingSphere {
+    center0: Point3,
+    center1: Point3,
+    time0: f64,
+    time1: f64,
+    radius: f64,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl MovingSphere {
+    pub fn new(
+        (center0, center1): (Point3, Point3),
+        (time0, time1): (f64, f64),
+        radius: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> MovingSphere {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            mat_ptr,
+        }
+    }
+
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 == self.time0 {
+            return self.center0.clone();
+        }
+        let s = (time - self.time0) / (self.time1 - self.time0);
+        &self.center0 + s * (&self.center1 - &self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let center = self.center(r.time());
+        let oc = r.origin() - &center;
+        let a = r.direction().norm();
+        let half_b = oc.dot(r.direction());
+        let c = oc.dot(&oc) - self.radius.powi(2);
+        let discriminant = half_b.powi(2) - a * c;
+        if discriminant < 0. {
+            return None;
+        }
+
+        let sqrtd = discriminant.sqrt();
+
+        // Find the nearest root that lies in the acceptable range.
+        let mut root = (-half_b - sqrtd) / a;
+        if root < t_min || t_max < root {
+            root = (-half_b + sqrtd) / a;
+            if root < t_min || t_max < root {
+                return None;
+            }
+        }
+
+        let p = r.at(root);
+        let outward_normal = (&p - &center) / self.radius;
+        Some(HitRecord::from_face_normal(
+            r,
+            p,
+            &outward_normal,
+            root,
+            get_sphere_uv(&outward_normal),
+            self.mat_ptr.clone(),
+        ))
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let r = self.radius.abs();
+        let r = Vec3::new(r, r, r);
+        let (c0, c1) = (self.center(time0), self.center(time1));
+        let box0 = Aabb::new(&c0 - &r, &c0 + &r);
+        let box1 = Aabb::new(&c1 - &r, &c1 + &r);
+        Some(Aabb::surrounding_box(&box0, &box1))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
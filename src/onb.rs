@@ -0,0 +1,46 @@
+use crate::hittable::HitRecord;
+use crate::vec3::Vec3;
+
+// Orthonormal basis, w is the "up" axis of local coordinates
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn build_from_w(n: &Vec3) -> Self {
+        let w = n.clone().normalize();
+        let a = if w.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(&a).normalize();
+        let u = w.cross(&v);
+        Onb { u, v, w }
+    }
+
+    // Shading frame of a hit, u follows its tangent when it has one
+    pub fn build_from_hit(rec: &HitRecord) -> Self {
+        let w = rec.normal.clone();
+        // Tangents vanish at poles, or may lie along the normal
+        match rec.tangent.as_ref().map(|t| t - &(t.dot(&w) * &w)) {
+            Some(u) if u.norm() > 1e-12 => {
+                let u = u.normalize();
+                let v = w.cross(&u);
+                Onb { u, v, w }
+            }
+            _ => Onb::build_from_w(&rec.normal),
+        }
+    }
+
+    pub fn local(&self, a: f64, b: f64, c: f64) -> Vec3 {
+        a * &self.u + b * &self.v + c * &self.w
+    }
+
+    // Inverse of local
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
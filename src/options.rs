@@ -0,0 +1,190 @@
+use crate::aov::Aov;
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Integrator {
+    Path,
+    Bdpt,
+    Photon,
+}
+
+impl FromStr for Integrator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "path" => Ok(Integrator::Path),
+            "bdpt" => Ok(Integrator::Bdpt),
+            "photon" => Ok(Integrator::Photon),
+            _ => Err(format!(
+                "unknown integrator '{}', expected one of: path, bdpt, photon",
+                s
+            )),
+        }
+    }
+}
+
+pub struct Options {
+    // Written to stdout
+    pub aov: Aov,
+    // Written next to the main image as <name>.ppm
+    pub layers: Vec<Aov>,
+    pub integrator: Integrator,
+    pub scene: String,
+    // Photons shot for the caustic map of the photon integrator
+    pub photons: usize,
+    // Photons per density estimate of the photon map, and how far to look
+    // for them, by default from the extent of the map
+    pub gather: usize,
+    pub gather_radius: Option<f64>,
+    // Traces wavelengths instead of RGB, for dispersion
+    pub spectral: bool,
+    // Renders an animated scene as <name>_<frame>.ppm files instead of one image
+    pub frames: Option<Range<usize>>,
+    pub fps: f64,
+    // Prints the memory use of scenes built from instances to stderr
+    pub memory_report: bool,
+}
+
+impl Options {
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            aov: Aov::Beauty,
+            layers: Vec::new(),
+            integrator: Integrator::Path,
+            scene: "random".to_string(),
+            photons: 200_000,
+            gather: 64,
+            gather_radius: None,
+            spectral: false,
+            frames: None,
+            fps: 24.0,
+            memory_report: false,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+            match arg.as_str() {
+                "--aov" => options.aov = value()?.parse()?,
+                "--layers" => {
+                    options.layers = value()?
+                        .split(',')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?
+                }
+                "--integrator" => options.integrator = value()?.parse()?,
+                "--scene" => options.scene = value()?,
+                "--photons" => {
+                    let photons = value()?;
+                    options.photons = photons
+                        .parse()
+                        .map_err(|_| format!("invalid photon count '{}'", photons))?
+                }
+                "--gather" => {
+                    let gather = value()?;
+                    options.gather = gather
+                        .parse()
+                        .ok()
+                        .filter(|&gather: &usize| gather > 0)
+                        .ok_or(format!("invalid gather count '{}'", gather))?
+                }
+                "--gather-radius" => {
+                    let radius = value()?;
+                    options.gather_radius = Some(
+                        radius
+                            .parse()
+                            .ok()
+                            .filter(|&radius: &f64| radius > 0.)
+                            .ok_or(format!("invalid gather radius '{}'", radius))?,
+                    )
+                }
+                "--spectral" => options.spectral = true,
+                "--frames" => options.frames = Some(parse_range(&value()?)?),
+                "--fps" => {
+                    let fps = value()?;
+                    options.fps = fps
+                        .parse()
+                        .ok()
+                        .filter(|&fps: &f64| fps > 0.)
+                        .ok_or(format!("invalid frame rate '{}'", fps))?
+                }
+                "--memory-report" => options.memory_report = true,
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+
+        if options.spectral && options.integrator != Integrator::Path {
+            return Err("--spectral is only supported by the path integrator".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+// START..END, END excluded
+fn parse_range(s: &str) -> Result<Range<usize>, String> {
+    let error = || format!("invalid frame range '{}', expected START..END", s);
+    let (start, end) = s.split_once("..").ok_or_else(error)?;
+    let start = start.parse().map_err(|_| error())?;
+    let end = end.parse().map_err(|_| error())?;
+    if start >= end {
+        return Err(error());
+    }
+    Ok(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn error(args: &[&str]) -> String {
+        parse(args).err().expect("arguments should be rejected")
+    }
+
+    #[test]
+    fn aov_and_layers() {
+        let options = parse(&["--aov", "normal", "--layers", "depth,material_id,cost"]).unwrap();
+        assert_eq!(options.aov, Aov::Normal);
+        assert_eq!(
+            options.layers,
+            vec![Aov::Depth, Aov::MaterialId, Aov::TraversalCost]
+        );
+
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.aov, Aov::Beauty);
+        assert!(options.layers.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(error(&["--layers", "depth,shiny"]).starts_with("unknown AOV 'shiny'"));
+        assert_eq!(error(&["--aov"]), "missing value for --aov");
+        assert_eq!(error(&["--samples", "4"]), "unknown argument '--samples'");
+    }
+
+    #[test]
+    fn integrator() {
+        assert_eq!(parse(&[]).unwrap().integrator, Integrator::Path);
+        let options = parse(&["--integrator", "bdpt"]).unwrap();
+        assert_eq!(options.integrator, Integrator::Bdpt);
+        assert!(error(&["--integrator", "mlt"]).starts_with("unknown integrator 'mlt'"));
+    }
+
+    #[test]
+    fn frames() {
+        let options = parse(&["--frames", "0..48", "--fps", "30"]).unwrap();
+        assert_eq!(options.frames, Some(0..48));
+        assert_eq!(options.fps, 30.0);
+        assert_eq!(parse(&[]).unwrap().frames, None);
+
+        for range in ["5..5", "7..3", "a..3", "3", "..4"].iter() {
+            assert!(error(&["--frames", range]).starts_with("invalid frame range"));
+        }
+        assert_eq!(error(&["--fps", "0"]), "invalid frame rate '0'");
+    }
+}
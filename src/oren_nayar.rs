@@ -0,0 +1,90 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::texture::Texture;
+use crate::Color;
+use crate::Ray;
+use crate::Vec3;
+use std::f64::consts::PI;
+
+// Rough diffuse surface of V-shaped Lambertian facets (Oren and Nayar 1994,
+// qualitative model), whose slopes have a standard deviation of sigma
+// degrees. Backscatters more than Lambertian, which sigma = 0 reduces to.
+pub struct OrenNayar {
+    albedo: Box<dyn Texture + Sync + Send>,
+    a: f64,
+    b: f64,
+}
+
+impl OrenNayar {
+    pub fn new(albedo: Color, sigma: f64) -> Self {
+        Self::textured(albedo, sigma)
+    }
+
+    pub fn textured(albedo: impl Texture + Sync + Send + 'static, sigma: f64) -> Self {
+        let sigma2 = sigma.to_radians().powi(2);
+        Self {
+            albedo: Box::new(albedo),
+            a: 1.0 - sigma2 / (2.0 * (sigma2 + 0.33)),
+            b: 0.45 * sigma2 / (sigma2 + 0.09),
+        }
+    }
+
+    // Reflectance over that of a Lambertian surface
+    fn factor(&self, n: &Vec3, wo: &Vec3, wi: &Vec3) -> f64 {
+        let (cos_o, cos_i) = (n.dot(wo), n.dot(wi));
+        let sin_o = (1.0 - cos_o * cos_o).max(0.0).sqrt();
+        let sin_i = (1.0 - cos_i * cos_i).max(0.0).sqrt();
+
+        // Cosine of the azimuth between the directions
+        let cos_phi = if sin_o > 1e-4 && sin_i > 1e-4 {
+            let to = wo - &(cos_o * n);
+            let ti = wi - &(cos_i * n);
+            (to.dot(&ti) / (sin_o * sin_i)).max(0.0)
+        } else {
+            0.0
+        };
+
+        // sin alpha and tan beta, alpha the larger polar angle
+        let (sin_alpha, tan_beta) = if cos_i > cos_o {
+            (sin_o, sin_i / cos_i)
+        } else {
+            (sin_i, sin_o / cos_o)
+        };
+        self.a + self.b * cos_phi * sin_alpha * tan_beta
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let mut scatter_direction = &rec.normal + Vec3::random_unit_vector(&mut rand::thread_rng());
+
+        // Catch degenerate scatter direction
+        if scatter_direction.near_zero() {
+            scatter_direction = rec.normal.clone();
+        }
+
+        // Cosine weighted like Lambertian, so only the factor remains
+        let wo = -r_in.direction().clone().normalize();
+        let factor = self.factor(&rec.normal, &wo, &scatter_direction.clone().normalize());
+        let scattered = Ray::new(rec.p.clone(), scatter_direction, r_in.time());
+        let attenuation = factor * &self.albedo.value(rec.u, rec.v, &rec.p);
+
+        Some((attenuation, scattered))
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        if rec.normal.dot(wo) > 0. && rec.normal.dot(wi) > 0. {
+            (self.factor(&rec.normal, wo, wi) / PI) * &self.albedo.value(rec.u, rec.v, &rec.p)
+        } else {
+            Color::new(0., 0., 0.)
+        }
+    }
+
+    fn pdf(&self, _wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        rec.normal.dot(wi).max(0.) / PI
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,77 @@
+use crate::vec3::{Point3, Vec3};
+use rand::seq::SliceRandom;
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise with random unit vectors on the lattice
+pub struct Perlin {
+    ranvec: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new() -> Self {
+        let mut rng = rand::thread_rng();
+        let ranvec = (0..POINT_COUNT)
+            .map(|_| Vec3::vec3_random_range(&mut rng, -1.0..1.0).normalize())
+            .collect();
+        let mut perm = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+        Perlin {
+            perm_x: perm(),
+            perm_y: perm(),
+            perm_z: perm(),
+            ranvec,
+        }
+    }
+
+    // In [-1, 1]
+    pub fn noise(&self, p: &Point3) -> f64 {
+        let u = p.x - p.x.floor();
+        let v = p.y - p.y.floor();
+        let w = p.z - p.z.floor();
+        let i = p.x.floor() as i64;
+        let j = p.y.floor() as i64;
+        let k = p.z.floor() as i64;
+
+        // Hermite smoothing of the interpolation weights
+        let uu = u * u * (3.0 - 2.0 * u);
+        let vv = v * v * (3.0 - 2.0 * v);
+        let ww = w * w * (3.0 - 2.0 * w);
+
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[((i + di) & 255) as usize]
+                        ^ self.perm_y[((j + dj) & 255) as usize]
+                        ^ self.perm_z[((k + dk) & 255) as usize];
+                    let (fi, fj, fk) = (di as f64, dj as f64, dk as f64);
+                    let weight = Vec3::new(u - fi, v - fj, w - fk);
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * self.ranvec[index].dot(&weight);
+                }
+            }
+        }
+        accum
+    }
+
+    // Sum of depth octaves of |noise|, in [0, 2)
+    pub fn turb(&self, p: &Point3, depth: usize) -> f64 {
+        let (accum, _, _) = (0..depth).fold((0.0, p.clone(), 1.0), |(accum, temp_p, weight), _| {
+            (
+                accum + weight * self.noise(&temp_p).abs(),
+                2.0 * temp_p,
+                weight * 0.5,
+            )
+        });
+        accum
+    }
+}
@@ -0,0 +1,366 @@
+// Caustic photon mapping (Jensen 1996).
+//
+// Photons are shot from the lights and followed through specular bounces
+// only; they are stored where they first land on a non-specular surface,
+// so the map holds exactly the L S+ D paths that are hard to find from the
+// camera. Rendering is a path tracer with next event estimation for direct
+// light, which adds a density estimate of the map at every diffuse vertex
+// and ignores emitters found after a diffuse bounce to avoid counting
+// caustics twice.
+
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vec3::{Color, Point3, Vec3};
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::PI;
+
+pub struct Photon {
+    p: Point3,
+    // Unit vector towards where the photon came from
+    wi: Vec3,
+    // Normal of the surface it landed on, facing the incoming photon
+    normal: Vec3,
+    power: Color,
+}
+
+// Balanced kd-tree stored as an implicit binary tree: the median of every
+// range is its root, the lower and upper halves are its children.
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    axes: Vec<usize>,
+    // Number of photons used for one density estimate
+    gather_count: usize,
+    max_radius: f64,
+}
+
+struct Neighbour {
+    dist_squared: f64,
+    index: usize,
+}
+
+impl PartialEq for Neighbour {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist_squared == other.dist_squared
+    }
+}
+
+impl Eq for Neighbour {}
+
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist_squared.total_cmp(&other.dist_squared)
+    }
+}
+
+impl PhotonMap {
+    // Without a gather radius, it is a small fraction of the extent of the
+    // photons, so that it follows the scale of the scene
+    pub fn new(
+        scene: &Scene,
+        photon_count: usize,
+        max_depth: usize,
+        gather_count: usize,
+        gather_radius: Option<f64>,
+    ) -> Self {
+        let photons: Vec<Photon> = (0..photon_count)
+            .into_par_iter()
+            .filter_map(|_| trace_photon(scene, photon_count, max_depth))
+            .collect();
+
+        let max_radius = gather_radius.unwrap_or_else(|| {
+            let (min, max) = bounds(&photons);
+            0.003 * (max - min).length()
+        });
+        let mut map = PhotonMap {
+            axes: vec![0; photons.len()],
+            photons,
+            gather_count,
+            max_radius,
+        };
+        let len = map.photons.len();
+        map.build(0, len);
+        map
+    }
+
+    fn build(&mut self, start: usize, end: usize) {
+        if end - start <= 1 {
+            return;
+        }
+        let range = &mut self.photons[start..end];
+        let (min, max) = bounds(range);
+        let extent = max - min;
+        let axis = if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        };
+
+        let mid = (end - start) / 2;
+        range.select_nth_unstable_by(mid, |a, b| a.p[axis].total_cmp(&b.p[axis]));
+        self.axes[start + mid] = axis;
+        self.build(start, start + mid);
+        self.build(start + mid + 1, end);
+    }
+
+    fn nearest(&self, p: &Point3, start: usize, end: usize, heap: &mut BinaryHeap<Neighbour>) {
+        if start >= end {
+            return;
+        }
+        let mid = start + (end - start) / 2;
+        let photon = &self.photons[mid];
+        let axis = self.axes[mid];
+        let delta = p[axis] - photon.p[axis];
+
+        let (near, far) = if delta < 0. {
+            ((start, mid), (mid + 1, end))
+        } else {
+            ((mid + 1, end), (start, mid))
+        };
+
+        self.nearest(p, near.0, near.1, heap);
+
+        let dist_squared = (&photon.p - p).norm();
+        if dist_squared < self.max_radius.powi(2) {
+            heap.push(Neighbour {
+                dist_squared,
+                index: mid,
+            });
+            if heap.len() > self.gather_count {
+                heap.pop();
+            }
+        }
+
+        let radius_squared = if heap.len() == self.gather_count {
+            heap.peek().unwrap().dist_squared
+        } else {
+            self.max_radius.powi(2)
+        };
+        if delta.powi(2) < radius_squared {
+            self.nearest(p, far.0, far.1, heap);
+        }
+    }
+
+    // Reflected radiance towards wo estimated from the nearest photons around rec.p
+    pub fn radiance(&self, rec: &HitRecord, wo: &Vec3) -> Color {
+        let mut heap = BinaryHeap::with_capacity(self.gather_count + 1);
+        self.nearest(&rec.p, 0, self.photons.len(), &mut heap);
+        if heap.is_empty() {
+            return Color::new(0., 0., 0.);
+        }
+        // Too few photons around means low density, not a small gather disc
+        let radius_squared = if heap.len() == self.gather_count {
+            heap.peek().unwrap().dist_squared
+        } else {
+            self.max_radius.powi(2)
+        };
+
+        heap.into_iter()
+            .map(|neighbour| &self.photons[neighbour.index])
+            .filter(|photon| photon.normal.dot(&rec.normal) > 0.9)
+            .fold(Color::new(0., 0., 0.), |acc, photon| {
+                acc + &rec.mat_ptr.eval(wo, &photon.wi, rec) * &photon.power
+            })
+            / (PI * radius_squared)
+    }
+}
+
+// Corners of the box around the photons
+fn bounds(photons: &[Photon]) -> (Point3, Point3) {
+    photons.iter().fold(
+        (
+            Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point3::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY),
+        ),
+        |(min, max), photon| {
+            (
+                Point3::new(
+                    min.x.min(photon.p.x),
+                    min.y.min(photon.p.y),
+                    min.z.min(photon.p.z),
+                ),
+                Point3::new(
+                    max.x.max(photon.p.x),
+                    max.y.max(photon.p.y),
+                    max.z.max(photon.p.z),
+                ),
+            )
+        },
+    )
+}
+
+fn trace_photon(scene: &Scene, photon_count: usize, max_depth: usize) -> Option<Photon> {
+    let (rec, pdf_pos) = scene.sample_light()?;
+
+    // Cosine weighted emission: the cosine cancels out with the density
+    let mut direction = &rec.normal + Vec3::random_unit_vector(&mut rand::thread_rng());
+    if direction.near_zero() {
+        direction = rec.normal.clone();
+    }
+    let mut power = (PI / (pdf_pos * photon_count as f64)) * &rec.mat_ptr.emitted(&rec);
+    let mut ray = Ray::new(rec.p, direction, scene.camera.sample_time());
+
+    for depth in 0..max_depth {
+        let rec = scene.world.hit(&ray, 0.001, f64::INFINITY)?;
+        if !rec.mat_ptr.is_specular() {
+            // Photons that reach a diffuse surface directly are direct light, not caustics.
+            // The map only covers surfaces, caustics inside media are left to the path tracer.
+            if depth == 0 || rec.mat_ptr.is_volumetric() {
+                return None;
+            }
+            return Some(Photon {
+                wi: -ray.direction().clone().normalize(),
+                p: rec.p,
+                normal: rec.normal,
+                power,
+            });
+        }
+        let (attenuation, scattered) = rec.mat_ptr.scatter(&ray, &rec)?;
+        power = &power * &attenuation;
+        ray = scattered;
+    }
+    None
+}
+
+// Light arriving at rec.p straight from a sampled light point, reflected towards wo
+fn direct_light(scene: &Scene, rec: &HitRecord, wo: &Vec3, time: f64) -> Color {
+    let (light, pdf_area) = match scene.sample_light() {
+        Some(v) => v,
+        None => return Color::new(0., 0., 0.),
+    };
+    let d = &light.p - &rec.p;
+    let dist = d.length();
+    let wi = d / dist;
+    let cos_light = -light.normal.dot(&wi);
+    // Transmitting materials are lit from behind too, eval is zero for the
+    // others
+    let cos_surface = if rec.mat_ptr.is_volumetric() {
+        1.0
+    } else {
+        rec.normal.dot(&wi).abs()
+    };
+    if cos_light <= 0. || cos_surface <= 0. {
+        return Color::new(0., 0., 0.);
+    }
+    let shadow = Ray::new(rec.p.clone(), wi.clone(), time);
+    if scene.world.hit(&shadow, 0.001, dist - 0.001).is_some() {
+        return Color::new(0., 0., 0.);
+    }
+    let g = cos_light * cos_surface / (dist * dist * pdf_area);
+    g * &(&rec.mat_ptr.eval(wo, &wi, rec) * &light.mat_ptr.emitted(&light))
+}
+
+// Whether light found by hitting an emitter is counted, or already covered
+// by direct light sampling or the caustic map
+#[derive(Clone, Copy, PartialEq)]
+enum Emission {
+    Counted,
+    Skipped,
+    // After a medium vertex, only light that went through specular bounces
+    // is missing, since photons are not stored in media
+    AfterSpecular,
+}
+
+pub fn photon_color(r: &Ray, scene: &Scene, map: &PhotonMap, depth: u64) -> Color {
+    trace(r, scene, map, depth, Emission::Counted)
+}
+
+fn trace(r: &Ray, scene: &Scene, map: &PhotonMap, depth: u64, emission: Emission) -> Color {
+    if depth == 0 {
+        return Color::new(0., 0., 0.);
+    }
+    let rec = match scene.world.hit(r, 0.001, f64::INFINITY) {
+        Some(rec) => rec,
+        None => return scene.background.color(r),
+    };
+
+    let emitted = if emission == Emission::Counted {
+        rec.mat_ptr.emitted(&rec)
+    } else {
+        Color::new(0., 0., 0.)
+    };
+
+    let (attenuation, scattered) = match rec.mat_ptr.scatter(r, &rec) {
+        Some(v) => v,
+        None => return emitted,
+    };
+
+    if rec.mat_ptr.is_specular() {
+        let emission = match emission {
+            Emission::AfterSpecular => Emission::Counted,
+            emission => emission,
+        };
+        emitted + attenuation * trace(&scattered, scene, map, depth - 1, emission)
+    } else {
+        let wo = -r.direction().clone().normalize();
+        let (caustics, emission) = if rec.mat_ptr.is_volumetric() {
+            (Color::new(0., 0., 0.), Emission::AfterSpecular)
+        } else {
+            (map.radiance(&rec, &wo), Emission::Skipped)
+        };
+        emitted
+            + direct_light(scene, &rec, &wo, r.time())
+            + caustics
+            + attenuation * trace(&scattered, scene, map, depth - 1, emission)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_point(rng: &mut StdRng) -> Point3 {
+        Point3::new(rng.gen(), rng.gen(), rng.gen())
+    }
+
+    #[test]
+    fn nearest_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let photons: Vec<Photon> = (0..2000)
+            .map(|_| Photon {
+                p: random_point(&mut rng),
+                wi: Vec3::new(0., 1., 0.),
+                normal: Vec3::new(0., 1., 0.),
+                power: Color::new(1., 1., 1.),
+            })
+            .collect();
+        let mut map = PhotonMap {
+            axes: vec![0; photons.len()],
+            photons,
+            gather_count: 16,
+            max_radius: 0.15,
+        };
+        let len = map.photons.len();
+        map.build(0, len);
+
+        for _ in 0..200 {
+            let p = random_point(&mut rng);
+            let mut heap = BinaryHeap::new();
+            map.nearest(&p, 0, len, &mut heap);
+            let mut found: Vec<f64> = heap.into_iter().map(|n| n.dist_squared).collect();
+            found.sort_by(f64::total_cmp);
+
+            let mut expected: Vec<f64> = map
+                .photons
+                .iter()
+                .map(|photon| (&photon.p - &p).norm())
+                .filter(|&d| d < map.max_radius.powi(2))
+                .collect();
+            expected.sort_by(f64::total_cmp);
+            expected.truncate(map.gather_count);
+            assert_eq!(found, expected);
+        }
+    }
+}
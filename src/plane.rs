@@ -0,0 +1,69 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::onb::Onb;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::sync::Arc;
+
+// Infinite plane through point, facing normal.
+// It has no bounding box, so the BVH tests it against every ray.
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    // Tangents for texture coordinates, repeating every unit length
+    axes: Onb,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Plane {
+    pub fn new(
+        point: Point3,
+        normal: Vec3,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Plane {
+        let axes = Onb::build_from_w(&normal);
+        Plane {
+            point,
+            normal: axes.w.clone(),
+            axes,
+            mat_ptr,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denom = self.normal.dot(r.direction());
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = self.normal.dot(&(&self.point - r.origin())) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let p = r.at(t);
+        let local = &p - &self.point;
+        let uv = (
+            local.dot(&self.axes.u).rem_euclid(1.0),
+            local.dot(&self.axes.v).rem_euclid(1.0),
+        );
+        Some(HitRecord::from_face_normal(
+            r,
+            p,
+            &self.normal,
+            t,
+            uv,
+            self.mat_ptr.clone(),
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        None
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
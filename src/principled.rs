@@ -0,0 +1,420 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::microfacet::{fresnel_dielectric, half_vector, Ggx};
+use crate::onb::Onb;
+use crate::texture::Texture;
+use crate::vec3::{Color, Vec3};
+use crate::Ray;
+use rand::Rng;
+use std::f64::consts::PI;
+
+// Lobes that scatter samples can be drawn from
+const DIFFUSE: usize = 0;
+const SPECULAR: usize = 1;
+const GLASS: usize = 2;
+const CLEARCOAT: usize = 3;
+
+fn schlick_weight(cos_theta: f64) -> f64 {
+    (1.0 - cos_theta).clamp(0.0, 1.0).powi(5)
+}
+
+fn lerp(t: f64, a: &Color, b: &Color) -> Color {
+    (1.0 - t) * a + t * b
+}
+
+fn luminance(c: &Color) -> f64 {
+    0.3 * c.x + 0.6 * c.y + 0.1 * c.z
+}
+
+fn reflect(wo: &Vec3, h: &Vec3) -> Vec3 {
+    2.0 * wo.dot(h) * h - wo.clone()
+}
+
+// Generalized Trowbridge-Reitz with exponent one, the long tailed
+// distribution of the clearcoat
+fn gtr1(cos_theta_h: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+    (a2 - 1.0) / (PI * a2.ln() * (1.0 + (a2 - 1.0) * cos_theta_h * cos_theta_h))
+}
+
+fn sample_gtr1(alpha: f64, u1: f64, u2: f64) -> Vec3 {
+    let a2 = alpha * alpha;
+    let cos_theta = ((1.0 - a2.powf(1.0 - u1)) / (1.0 - a2)).max(0.0).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * u2;
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+// Disney's principled BSDF (Burley 2012 and 2015): a diffuse base with
+// retro-reflection and sheen, an anisotropic GGX specular layer that turns
+// into metal with metallic, rough glass for transmission, and a clearcoat.
+// All parameters are in [0, 1]. Base color, metallic and roughness can come
+// from textures, gray ones being read from their red channel.
+pub struct Principled {
+    base_color: Box<dyn Texture + Sync + Send>,
+    metallic: Box<dyn Texture + Sync + Send>,
+    roughness: Box<dyn Texture + Sync + Send>,
+    specular: f64,
+    specular_tint: f64,
+    anisotropic: f64,
+    sheen: f64,
+    clearcoat: f64,
+    clearcoat_gloss: f64,
+    transmission: f64,
+    index_of_refraction: f64,
+}
+
+// Parameters looked up at a hit, with directions in a local frame whose z
+// axis is on the side of wo
+struct Shading {
+    color: Color,
+    metallic: f64,
+    roughness: f64,
+    distribution: Ggx,
+    // Beyond the surface over in front of it
+    eta: f64,
+    // wo is within the object, where only the glass lobe is seen
+    inside: bool,
+}
+
+impl Principled {
+    pub fn new(base_color: Color) -> Self {
+        Self::textured(base_color)
+    }
+
+    pub fn textured(base_color: impl Texture + Sync + Send + 'static) -> Self {
+        Self {
+            base_color: Box::new(base_color),
+            metallic: Box::new(Color::new(0.0, 0.0, 0.0)),
+            roughness: Box::new(Color::new(0.5, 0.5, 0.5)),
+            specular: 0.5,
+            specular_tint: 0.0,
+            anisotropic: 0.0,
+            sheen: 0.0,
+            clearcoat: 0.0,
+            clearcoat_gloss: 1.0,
+            transmission: 0.0,
+            index_of_refraction: 1.5,
+        }
+    }
+
+    pub fn with_metallic(self, metallic: f64) -> Self {
+        self.with_metallic_texture(Color::new(metallic, metallic, metallic))
+    }
+
+    pub fn with_metallic_texture(mut self, metallic: impl Texture + Sync + Send + 'static) -> Self {
+        self.metallic = Box::new(metallic);
+        self
+    }
+
+    pub fn with_roughness(self, roughness: f64) -> Self {
+        self.with_roughness_texture(Color::new(roughness, roughness, roughness))
+    }
+
+    pub fn with_roughness_texture(
+        mut self,
+        roughness: impl Texture + Sync + Send + 'static,
+    ) -> Self {
+        self.roughness = Box::new(roughness);
+        self
+    }
+
+    // Reflectance at normal incidence of dielectrics, 0.5 being 4%, and how
+    // much of it takes the hue of the base color
+    pub fn with_specular(mut self, specular: f64, tint: f64) -> Self {
+        self.specular = specular;
+        self.specular_tint = tint;
+        self
+    }
+
+    // Highlights stretched along the tangent of the surface
+    pub fn with_anisotropic(mut self, anisotropic: f64) -> Self {
+        self.anisotropic = anisotropic;
+        self
+    }
+
+    // Grazing reflection of cloth
+    pub fn with_sheen(mut self, sheen: f64) -> Self {
+        self.sheen = sheen;
+        self
+    }
+
+    // A second, colorless specular layer, glossier with gloss
+    pub fn with_clearcoat(mut self, clearcoat: f64, gloss: f64) -> Self {
+        self.clearcoat = clearcoat;
+        self.clearcoat_gloss = gloss;
+        self
+    }
+
+    // Share of the dielectric base that is rough glass of the given index
+    pub fn with_transmission(mut self, transmission: f64, index_of_refraction: f64) -> Self {
+        self.transmission = transmission;
+        self.index_of_refraction = index_of_refraction;
+        self
+    }
+
+    // Frame of the hit, with the normal turned towards wo
+    fn frame(rec: &HitRecord, wo: &Vec3) -> (Onb, bool) {
+        let frame = Onb::build_from_hit(rec);
+        let flipped = wo.dot(&frame.w) < 0.0;
+        (frame, flipped)
+    }
+
+    fn to_local(frame: &Onb, flipped: bool, w: &Vec3) -> Vec3 {
+        let w = frame.to_local(w);
+        if flipped {
+            Vec3::new(w.x, w.y, -w.z)
+        } else {
+            w
+        }
+    }
+
+    fn shading(&self, rec: &HitRecord, flipped: bool) -> Shading {
+        // The normal of a hit faces the incoming ray, out of the object on
+        // front faces
+        let inside = flipped == rec.front_face;
+        let roughness = self.roughness.value(rec.u, rec.v, &rec.p).x.clamp(0.0, 1.0);
+        Shading {
+            color: self.base_color.value(rec.u, rec.v, &rec.p),
+            metallic: self.metallic.value(rec.u, rec.v, &rec.p).x.clamp(0.0, 1.0),
+            roughness,
+            distribution: Ggx::anisotropic(roughness, self.anisotropic),
+            eta: if inside {
+                1.0 / self.index_of_refraction
+            } else {
+                self.index_of_refraction
+            },
+            inside,
+        }
+    }
+
+    fn glass_weight(&self, s: &Shading) -> f64 {
+        (1.0 - s.metallic) * self.transmission
+    }
+
+    // Chance that the glass lobe reflects rather than transmits, with
+    // fresnel that of the microfacet. Within the object the share of light
+    // the surface does not let out is reflected back.
+    fn glass_reflectance(&self, s: &Shading, fresnel: f64) -> f64 {
+        if s.inside {
+            1.0 - self.glass_weight(s) * (1.0 - fresnel)
+        } else {
+            fresnel
+        }
+    }
+
+    // Probabilities of picking each lobe when sampling
+    fn lobe_weights(&self, s: &Shading) -> [f64; 4] {
+        if s.inside {
+            return [0.0, 0.0, 1.0, 0.0];
+        }
+        let glass = self.glass_weight(s);
+        let weights = [
+            (1.0 - s.metallic) * (1.0 - self.transmission),
+            (1.0 - glass) * (0.25 + 0.75 * s.metallic),
+            glass,
+            0.25 * self.clearcoat,
+        ];
+        let total: f64 = weights.iter().sum();
+        weights.map(|w| w / total)
+    }
+
+    fn clearcoat_alpha(&self) -> f64 {
+        0.1 + (0.001 - 0.1) * self.clearcoat_gloss
+    }
+
+    fn specular_color(&self, s: &Shading) -> Color {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let lum = luminance(&s.color);
+        let tint = if lum > 0.0 {
+            s.color.clone() / lum
+        } else {
+            white.clone()
+        };
+        let dielectric = 0.08 * self.specular * &lerp(self.specular_tint, &white, &tint);
+        lerp(s.metallic, &dielectric, &s.color)
+    }
+
+    fn eval_local(&self, s: &Shading, wo: &Vec3, wi: &Vec3) -> Color {
+        let black = Color::new(0.0, 0.0, 0.0);
+        if wo.z <= 0.0 || wi.z == 0.0 {
+            return black;
+        }
+        let glass = self.glass_weight(s);
+
+        if wi.z < 0.0 {
+            let h = match half_vector(wo, wi, s.eta) {
+                Some(h) => h,
+                None => return black,
+            };
+            let fresnel = fresnel_dielectric(wo.dot(&h), s.eta);
+            let denom = wo.dot(&h) + s.eta * wi.dot(&h);
+            let f = glass
+                * (1.0 - fresnel)
+                * s.distribution.d(&h)
+                * s.distribution.g(wo, wi)
+                * (wo.dot(&h) * wi.dot(&h)).abs()
+                * s.eta
+                * s.eta
+                / ((wo.z * wi.z).abs() * denom * denom);
+            // Tinted on the way in and out, by the base color overall
+            let tint = Color::new(s.color.x.sqrt(), s.color.y.sqrt(), s.color.z.sqrt());
+            return f * &tint;
+        }
+
+        let h = (wo + wi).normalize();
+        let cos_theta_d = wi.dot(&h);
+        let fresnel = fresnel_dielectric(wo.dot(&h), s.eta);
+        let microfacet = s.distribution.d(&h) * s.distribution.g(wo, wi) / (4.0 * wo.z * wi.z);
+        let glass_reflection = if s.inside {
+            microfacet * self.glass_reflectance(s, fresnel)
+        } else {
+            microfacet * glass * fresnel
+        };
+        if s.inside {
+            return Color::new(glass_reflection, glass_reflection, glass_reflection);
+        }
+
+        // Diffuse with retro-reflection at grazing angles on rough surfaces
+        let diffuse_weight = (1.0 - s.metallic) * (1.0 - self.transmission);
+        let (fl, fv) = (schlick_weight(wi.z), schlick_weight(wo.z));
+        let fd90 = 0.5 + 2.0 * s.roughness * cos_theta_d * cos_theta_d;
+        let fd = (1.0 + (fd90 - 1.0) * fl) * (1.0 + (fd90 - 1.0) * fv);
+        let sheen_color = lerp(0.5, &Color::new(1.0, 1.0, 1.0), &s.color);
+        let diffuse = diffuse_weight
+            * &(fd / PI * &s.color + self.sheen * schlick_weight(cos_theta_d) * &sheen_color);
+
+        let f0 = self.specular_color(s);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let schlick = lerp(schlick_weight(wo.dot(&h)), &f0, &white);
+        let specular = (microfacet * (1.0 - glass)) * &schlick;
+
+        // Fixed index of 1.5, masking of a GGX with alpha 0.25
+        let clearcoat = 0.25
+            * self.clearcoat
+            * gtr1(h.z, self.clearcoat_alpha())
+            * Ggx::new(0.5).g(wo, wi)
+            * (0.04 + 0.96 * schlick_weight(wo.dot(&h)))
+            / (4.0 * wo.z * wi.z);
+
+        diffuse
+            + specular
+            + Color::new(
+                glass_reflection + clearcoat,
+                glass_reflection + clearcoat,
+                glass_reflection + clearcoat,
+            )
+    }
+
+    fn pdf_local(&self, s: &Shading, wo: &Vec3, wi: &Vec3) -> f64 {
+        if wo.z <= 0.0 || wi.z == 0.0 {
+            return 0.0;
+        }
+        let weights = self.lobe_weights(s);
+
+        if wi.z < 0.0 {
+            let h = match half_vector(wo, wi, s.eta) {
+                Some(h) => h,
+                None => return 0.0,
+            };
+            let fresnel = fresnel_dielectric(wo.dot(&h), s.eta);
+            let denom = wo.dot(&h) + s.eta * wi.dot(&h);
+            return weights[GLASS]
+                * (1.0 - self.glass_reflectance(s, fresnel))
+                * s.distribution.pdf_visible(wo, &h)
+                * s.eta
+                * s.eta
+                * wi.dot(&h).abs()
+                / (denom * denom);
+        }
+
+        let h = (wo + wi).normalize();
+        let fresnel = fresnel_dielectric(wo.dot(&h), s.eta);
+        let reflection = s.distribution.pdf_visible(wo, &h) / (4.0 * wo.dot(&h));
+        weights[DIFFUSE] * wi.z / PI
+            + weights[SPECULAR] * reflection
+            + weights[GLASS] * self.glass_reflectance(s, fresnel) * reflection
+            + weights[CLEARCOAT] * gtr1(h.z, self.clearcoat_alpha()) * h.z / (4.0 * wo.dot(&h))
+    }
+
+    fn sample_local(&self, s: &Shading, wo: &Vec3, rng: &mut impl Rng) -> Option<Vec3> {
+        let weights = self.lobe_weights(s);
+        let mut u = rng.gen::<f64>();
+        let lobe = (0..weights.len())
+            .find(|&i| {
+                u -= weights[i];
+                u < 0.0
+            })
+            .unwrap_or(GLASS);
+
+        let wi = match lobe {
+            DIFFUSE => &Vec3::new(0.0, 0.0, 1.0) + Vec3::random_unit_vector(rng),
+            SPECULAR => reflect(wo, &s.distribution.sample_visible(wo, rng.gen(), rng.gen())),
+            CLEARCOAT => reflect(
+                wo,
+                &sample_gtr1(self.clearcoat_alpha(), rng.gen(), rng.gen()),
+            ),
+            _ => {
+                let h = s.distribution.sample_visible(wo, rng.gen(), rng.gen());
+                let cos_theta_o = wo.dot(&h);
+                let fresnel = fresnel_dielectric(cos_theta_o, s.eta);
+                if rng.gen::<f64>() < self.glass_reflectance(s, fresnel) {
+                    reflect(wo, &h)
+                } else {
+                    // No total internal reflection here, fresnel would be 1
+                    let sin2_theta_t = (1.0 - cos_theta_o * cos_theta_o) / (s.eta * s.eta);
+                    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+                    let wi = (cos_theta_o / s.eta - cos_theta_t) * &h - wo.clone() / s.eta;
+                    return (wi.z < 0.0).then_some(wi);
+                }
+            }
+        };
+        (wi.z > 0.0).then(|| wi.normalize())
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let mut rng = rand::thread_rng();
+        let wo = -r_in.direction().clone().normalize();
+        let (frame, flipped) = Self::frame(rec, &wo);
+        let s = self.shading(rec, flipped);
+        let wo = Self::to_local(&frame, flipped, &wo);
+
+        let wi = self.sample_local(&s, &wo, &mut rng)?;
+        // f cos / pdf of all the lobes together, that might have picked wi
+        let pdf = self.pdf_local(&s, &wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let attenuation = (wi.z.abs() / pdf) * &self.eval_local(&s, &wo, &wi);
+
+        let z = if flipped { -wi.z } else { wi.z };
+        let scattered = Ray::new(rec.p.clone(), frame.local(wi.x, wi.y, z), r_in.time());
+        Some((attenuation, scattered))
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        let (frame, flipped) = Self::frame(rec, wo);
+        let s = self.shading(rec, flipped);
+        let (wo, wi) = (
+            Self::to_local(&frame, flipped, wo),
+            Self::to_local(&frame, flipped, wi),
+        );
+        self.eval_local(&s, &wo, &wi)
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        let (frame, flipped) = Self::frame(rec, wo);
+        let s = self.shading(rec, flipped);
+        let (wo, wi) = (
+            Self::to_local(&frame, flipped, wo),
+            Self::to_local(&frame, flipped, wi),
+        );
+        self.pdf_local(&s, &wo, &wi)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,165 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use rand::Rng;
+use std::sync::Arc;
+
+// Parallelogram spanned by the edges u and v from the corner q.
+// The outward normal is u x v, and (u, v) texture coordinates run along the edges.
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    normal: Vec3,
+    // Plane equation normal . p = d
+    d: f64,
+    // Maps a point of the plane to its edge coordinates
+    w: Vec3,
+    area: f64,
+}
+
+impl Quad {
+    pub fn new(
+        q: Point3,
+        u: Vec3,
+        v: Vec3,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Quad {
+        let n = u.cross(&v);
+        let area = n.length();
+        let normal = n.clone() / area;
+        let d = normal.dot(&q);
+        let w = &n / n.norm();
+        Quad {
+            q,
+            u,
+            v,
+            mat_ptr,
+            normal,
+            d,
+            w,
+            area,
+        }
+    }
+
+    // Axis-aligned rectangles at z = k, y = k and x = k, facing the positive axis
+
+    pub fn xy_rect(
+        (x0, x1): (f64, f64),
+        (y0, y1): (f64, f64),
+        k: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Quad {
+        Quad::new(
+            Point3::new(x0, y0, k),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            mat_ptr,
+        )
+    }
+
+    pub fn xz_rect(
+        (x0, x1): (f64, f64),
+        (z0, z1): (f64, f64),
+        k: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Quad {
+        Quad::new(
+            Point3::new(x0, k, z0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            mat_ptr,
+        )
+    }
+
+    pub fn yz_rect(
+        (y0, y1): (f64, f64),
+        (z0, z1): (f64, f64),
+        k: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Quad {
+        Quad::new(
+            Point3::new(k, y0, z0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            mat_ptr,
+        )
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denom = self.normal.dot(r.direction());
+
+        // No hit if the ray is parallel to the plane.
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(r.origin())) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        // Edge coordinates of the hit point decide if it lies inside
+        let p = r.at(t);
+        let planar_hitpt = &p - &self.q;
+        let alpha = self.w.dot(&planar_hitpt.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar_hitpt));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(HitRecord::from_face_normal(
+            r,
+            p,
+            &self.normal,
+            t,
+            (alpha, beta),
+            self.mat_ptr.clone(),
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let corners = [
+            &self.q + &self.u,
+            &self.q + &self.v,
+            &self.q + &self.u + &self.v,
+        ];
+        let bbox = corners
+            .iter()
+            .fold(Aabb::new(self.q.clone(), self.q.clone()), |acc, c| {
+                Aabb::surrounding_box(&acc, &Aabb::new(c.clone(), c.clone()))
+            });
+        Some(bbox.pad(0.0001))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+
+    fn sample_surface(&self) -> Option<HitRecord> {
+        let mut rng = rand::thread_rng();
+        let (alpha, beta) = (rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+        Some(HitRecord {
+            p: &self.q + alpha * &self.u + beta * &self.v,
+            normal: self.normal.clone(),
+            mat_ptr: self.mat_ptr.clone(),
+            t: 0.0,
+            u: alpha,
+            v: beta,
+            front_face: true,
+            object_id: None,
+            material_id: None,
+            tangent: None,
+            collision_weight: None,
+        })
+    }
+
+    fn area(&self) -> f64 {
+        self.area
+    }
+}
@@ -1,13 +1,19 @@
 use crate::vec3::{Point3, Vec3};
 
+#[derive(Clone)]
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
+    pub time: f64,
 }
 
 impl Ray {
-    pub fn new(origin: Point3, direction: Vec3) -> Self {
-        Ray { origin, direction }
+    pub fn new(origin: Point3, direction: Vec3, time: f64) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
     pub fn at(&self, t: f64) -> Point3 {
@@ -21,6 +27,10 @@ impl Ray {
     pub fn direction(&self) -> &Vec3 {
         &self.direction
     }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
 }
 
 // Ray reflect
@@ -32,6 +42,6 @@ pub fn reflect(v: &Vec3, normal: &Vec3) -> Vec3 {
 pub fn refract(uv: &Vec3, normal: &Vec3, eta_i_over_eta_t: f64) -> Vec3 {
     let cos_theta = (-1.0 * uv).dot(normal).min(1.0);
     let r_out_perp = eta_i_over_eta_t * (uv + cos_theta * normal);
-    let r_out_parallel = (1.0 - r_out_perp.norm()).abs().sqrt() * -1.0 * normal;
+    let r_out_parallel = -(1.0 - r_out_perp.norm()).abs().sqrt() * normal;
     r_out_perp + r_out_parallel
 }
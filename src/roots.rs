@@ -0,0 +1,192 @@
+// Real roots of polynomials up to degree four, in ascending order.
+// Coefficients are given from the highest degree down.
+
+const EPSILON: f64 = 1e-9;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+pub fn quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0. {
+        return if b == 0. { Vec::new() } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0. {
+        return Vec::new();
+    }
+    // Avoids cancellation between b and the square root
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0. {
+        return vec![0.0];
+    }
+    let (x0, x1) = (q / a, c / q);
+    if x0 < x1 {
+        vec![x0, x1]
+    } else {
+        vec![x1, x0]
+    }
+}
+
+// Cardano's method on the depressed cubic
+pub fn cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a == 0. {
+        return quadratic(b, c, d);
+    }
+    let (a, b, c) = (b / a, c / a, d / a);
+
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let mut roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0. {
+        // Three real roots
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + std::f64::consts::PI / 3.0).cos(),
+            -t * (phi - std::f64::consts::PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+
+    roots.iter_mut().for_each(|x| *x -= a / 3.0);
+    roots.sort_by(|x, y| x.total_cmp(y));
+    roots
+}
+
+// Ferrari's method, with every root refined by a few Newton steps since
+// the closed form loses precision quickly
+pub fn quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a == 0. {
+        return cubic(b, c, d, e);
+    }
+    let (a, b, c, d) = (b / a, c / a, d / a, e / a);
+
+    // Depressed quartic y^4 + p y^2 + q y + r
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c / 4.0 + d;
+
+    let mut roots = if is_zero(r) {
+        let mut roots = cubic(1.0, 0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // Any real root of the resolvent cubic splits it into two quadratics
+        let z = cubic(1.0, -p / 2.0, -r, r * p / 2.0 - q * q / 8.0)[0];
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if is_zero(u) {
+            0.0
+        } else if u > 0. {
+            u.sqrt()
+        } else {
+            return Vec::new();
+        };
+        let v = if is_zero(v) {
+            0.0
+        } else if v > 0. {
+            v.sqrt()
+        } else {
+            return Vec::new();
+        };
+        let v = if q < 0. { -v } else { v };
+        let mut roots = quadratic(1.0, v, z - u);
+        roots.extend(quadratic(1.0, -v, z + u));
+        roots
+    };
+
+    let f = |x: f64| (((x + a) * x + b) * x + c) * x + d;
+    let df = |x: f64| ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+    for x in roots.iter_mut() {
+        *x -= a / 4.0;
+        for _ in 0..2 {
+            let slope = df(*x);
+            if slope != 0. {
+                *x -= f(*x) / slope;
+            }
+        }
+    }
+    roots.sort_by(|x, y| x.total_cmp(y));
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(found: Vec<f64>, expected: &[f64]) {
+        assert_eq!(found.len(), expected.len(), "roots {:?}", found);
+        for (x, y) in found.iter().zip(expected) {
+            assert!(
+                (x - y).abs() < 1e-6,
+                "roots {:?}, expected {:?}",
+                found,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        // (x - 1)(x - 3)
+        assert_roots(quadratic(1.0, -4.0, 3.0), &[1.0, 3.0]);
+        // 2(x + 0.5)(x - 2)
+        assert_roots(quadratic(2.0, -3.0, -2.0), &[-0.5, 2.0]);
+        assert_roots(quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(quadratic(0.0, 2.0, -1.0), &[0.5]);
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        // (x - 1)(x^2 + x + 1)
+        assert_roots(cubic(1.0, 0.0, 0.0, -1.0), &[1.0]);
+        // -2(x + 2)x(x - 0.5)
+        assert_roots(cubic(-2.0, -3.0, 2.0, 0.0), &[-2.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // (x^2 - 4)(x^2 + 1)
+        assert_roots(quartic(1.0, 0.0, -3.0, 0.0, -4.0), &[-2.0, 2.0]);
+        assert_roots(quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[]);
+        // Torus around z with radii 2 and 0.5, (|p|^2 + R^2 - r^2)^2 =
+        // 4 R^2 (x^2 + y^2), against a ray along z at x = 2 starting from
+        // z = -5: it crosses the tube at z = -0.5 and 0.5
+        let (big, small, x) = (2.0_f64, 0.5_f64, 2.0_f64);
+        let k = x * x + big * big - small * small;
+        // (z^2 + k)^2 - 4 R^2 x^2 expanded in t, with z = t - 5
+        let c = k * k - 4.0 * big * big * x * x;
+        assert_roots(
+            quartic(
+                1.0,
+                -20.0,
+                150.0 + 2.0 * k,
+                -500.0 - 20.0 * k,
+                625.0 + 50.0 * k + c,
+            ),
+            &[4.5, 5.5],
+        );
+    }
+}
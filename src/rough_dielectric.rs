@@ -0,0 +1,134 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::microfacet::{fresnel_dielectric, half_vector, Ggx};
+use crate::onb::Onb;
+use crate::vec3::Vec3;
+use crate::Color;
+use crate::Ray;
+use rand::Rng;
+
+// Frosted glass: GGX microfacets that reflect or refract by the exact Fresnel
+// equations (Walter et al. 2007). Like Dielectrics, transmission leaves the
+// path throughput unscaled by the ratio of indices, so the same BSDF serves
+// camera and light paths.
+pub struct RoughDielectric {
+    index_of_refraction: f64,
+    distribution: Ggx,
+}
+
+impl RoughDielectric {
+    pub fn new(index_of_refraction: f64, roughness: f64) -> Self {
+        Self {
+            index_of_refraction,
+            distribution: Ggx::new(roughness),
+        }
+    }
+
+    // Index beyond the surface over the one on the side of the normal, which
+    // faces the incoming ray
+    fn eta(&self, rec: &HitRecord) -> f64 {
+        if rec.front_face {
+            self.index_of_refraction
+        } else {
+            1.0 / self.index_of_refraction
+        }
+    }
+
+    // Local directions mirrored so that wo is on the side of the normal, with
+    // the ratio of indices as seen from there. Reverse densities ask for wo
+    // beyond the surface.
+    fn oriented(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> (Vec3, Vec3, f64) {
+        let frame = Onb::build_from_hit(rec);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z < 0. {
+            let flip = |w: Vec3| Vec3::new(w.x, w.y, -w.z);
+            (flip(wo), flip(wi), 1. / self.eta(rec))
+        } else {
+            (wo, wi, self.eta(rec))
+        }
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        let mut rng = rand::thread_rng();
+        let frame = Onb::build_from_hit(rec);
+        let wo = frame.to_local(&-r_in.direction().clone().normalize());
+        if wo.z <= 0. {
+            return None;
+        }
+        let eta = self.eta(rec);
+
+        let h = self.distribution.sample_visible(&wo, rng.gen(), rng.gen());
+        let cos_theta_o = wo.dot(&h);
+        let fresnel = fresnel_dielectric(cos_theta_o, eta);
+        let wi = if rng.gen::<f64>() < fresnel {
+            let wi = 2. * cos_theta_o * &h - wo.clone();
+            if wi.z <= 0. {
+                return None;
+            }
+            wi
+        } else {
+            // No total internal reflection here, fresnel would be 1
+            let sin2_theta_t = (1. - cos_theta_o * cos_theta_o) / (eta * eta);
+            let cos_theta_t = (1. - sin2_theta_t).sqrt();
+            let wi = (cos_theta_o / eta - cos_theta_t) * &h - wo.clone() / eta;
+            if wi.z >= 0. {
+                return None;
+            }
+            wi
+        };
+
+        // f cos / pdf: the Fresnel factors cancel with the choice of lobe, D
+        // and G1(wo) with the sampling of visible normals
+        let attenuation = self.distribution.g(&wo, &wi) / self.distribution.g1(&wo);
+        let scattered = Ray::new(rec.p.clone(), frame.local(wi.x, wi.y, wi.z), r_in.time());
+        Some((Color::new(attenuation, attenuation, attenuation), scattered))
+    }
+
+    fn eval(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> Color {
+        let (wo, wi, eta) = self.oriented(wo, wi, rec);
+        if wo.z == 0. || wi.z == 0. {
+            return Color::new(0., 0., 0.);
+        }
+        let h = match half_vector(&wo, &wi, eta) {
+            Some(h) => h,
+            None => return Color::new(0., 0., 0.),
+        };
+
+        let fresnel = fresnel_dielectric(wo.dot(&h), eta);
+        let dg = self.distribution.d(&h) * self.distribution.g(&wo, &wi);
+        let f = if wi.z > 0. {
+            fresnel * dg / (4. * wo.z * wi.z)
+        } else {
+            let denom = wo.dot(&h) + eta * wi.dot(&h);
+            (1. - fresnel) * dg * (wo.dot(&h) * wi.dot(&h)).abs() * eta * eta
+                / ((wo.z * wi.z).abs() * denom * denom)
+        };
+        Color::new(f, f, f)
+    }
+
+    fn pdf(&self, wo: &Vec3, wi: &Vec3, rec: &HitRecord) -> f64 {
+        let (wo, wi, eta) = self.oriented(wo, wi, rec);
+        if wo.z == 0. || wi.z == 0. {
+            return 0.;
+        }
+        let h = match half_vector(&wo, &wi, eta) {
+            Some(h) => h,
+            None => return 0.,
+        };
+
+        let fresnel = fresnel_dielectric(wo.dot(&h), eta);
+        let pdf_h = self.distribution.pdf_visible(&wo, &h);
+        if wi.z > 0. {
+            fresnel * pdf_h / (4. * wo.dot(&h))
+        } else {
+            let denom = wo.dot(&h) + eta * wi.dot(&h);
+            (1. - fresnel) * pdf_h * eta * eta * wi.dot(&h).abs() / (denom * denom)
+        }
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
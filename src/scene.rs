@@ -0,0 +1,1824 @@
+// Parity tests stay `% 2 == 0`, usize::is_multiple_of needs Rust 1.87
+#![allow(clippy::manual_is_multiple_of)]
+
+use crate::aabb::Aabb;
+use crate::animation::{Animation, CameraTrack, Interpolation, Track, TransformTrack};
+use crate::box_shape::BoxShape;
+use crate::bvh::BvhNode;
+use crate::camera::Camera;
+use crate::coated::Coated;
+use crate::conductor::Conductor;
+use crate::cone::Cone;
+use crate::constant_medium::ConstantMedium;
+use crate::csg::Csg;
+use crate::curve::{Curve, CurveMode};
+use crate::cylinder::Cylinder;
+use crate::density_field::{NoiseDensity, VoxelGrid};
+use crate::dielectrics::Dielectrics;
+use crate::diffuse_light::DiffuseLight;
+use crate::disk::Disk;
+use crate::hair::Hair;
+use crate::heightfield::Heightfield;
+use crate::heterogeneous_medium::HeterogeneousMedium;
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::instance::Instance;
+use crate::lambertian::Lambertian;
+use crate::material::{Material, MaterialIds};
+use crate::metaballs::Metaballs;
+use crate::metal::Metal;
+use crate::mix_material::MixMaterial;
+use crate::moving_sphere::MovingSphere;
+use crate::oren_nayar::OrenNayar;
+use crate::perlin::Perlin;
+use crate::plane::Plane;
+use crate::principled::Principled;
+use crate::quad::Quad;
+use crate::ray::Ray;
+use crate::rough_dielectric::RoughDielectric;
+use crate::sdf;
+use crate::sdf_shape::SdfShape;
+use crate::sphere::Sphere;
+use crate::subsurface::Subsurface;
+use crate::texture::ImageTexture;
+use crate::thin_film::ThinFilm;
+use crate::tlas::{Blas, MemoryReport, Tlas};
+use crate::torus::Torus;
+use crate::transform::Transform;
+use crate::vec3::{Color, Point3, Vec3};
+
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub enum Background {
+    Sky,
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(&self, r: &Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let unit_direction = r.direction().clone().normalize();
+                let t = 0.5 * (unit_direction.y + 1.0);
+                (1. - t) * Color::new(1., 1., 1.) + t * Color::new(0.5, 0.7, 1.)
+            }
+            Background::Solid(color) => color.clone(),
+        }
+    }
+}
+
+pub struct Scene {
+    pub world: BvhNode,
+    // Emitting objects of the world, duplicated so that they can be sampled directly.
+    // The background is not sampled, only found by escaping rays.
+    pub lights: Vec<Box<dyn Hittable + Sync + Send + 'static>>,
+    pub background: Background,
+    pub camera: Camera,
+    // Of the two-level structure, for scenes built from instances
+    pub memory_report: Option<MemoryReport>,
+    material_ids: MaterialIds,
+}
+
+impl Scene {
+    // The world's BVH bounds moving objects over the camera shutter
+    pub fn new(
+        world: HittableList,
+        lights: Vec<Box<dyn Hittable + Sync + Send + 'static>>,
+        background: Background,
+        camera: Camera,
+    ) -> Self {
+        let (time0, time1) = camera.shutter();
+        // Numbered before the BVH reorders the objects
+        let mut material_ids = MaterialIds::default();
+        world.number_materials(&mut material_ids);
+        Scene {
+            world: BvhNode::new(world, time0, time1),
+            lights,
+            background,
+            camera,
+            memory_report: None,
+            material_ids,
+        }
+    }
+
+    pub fn with_memory_report(mut self, memory_report: MemoryReport) -> Self {
+        self.memory_report = Some(memory_report);
+        self
+    }
+
+    // Hit on the world, with the material numbered
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut rec = self.world.hit(r, t_min, t_max)?;
+        if rec.material_id.is_none() {
+            rec.material_id = self.material_ids.get(&rec.mat_ptr);
+        }
+        Some(rec)
+    }
+
+    // Picks a light uniformly, then a point on it.
+    // Returns the point and its density per unit area.
+    pub fn sample_light(&self) -> Option<(HitRecord, f64)> {
+        if self.lights.is_empty() {
+            return None;
+        }
+        let light = &self.lights[rand::thread_rng().gen_range(0..self.lights.len())];
+        let rec = light.sample_surface()?;
+        Some((rec, 1.0 / (self.lights.len() as f64 * light.area())))
+    }
+
+    // Density per unit area with which sample_light returns p, a point seen from origin
+    pub fn light_pdf(&self, origin: &Point3, p: &Point3) -> f64 {
+        // Lights do not move
+        let r = Ray::new(origin.clone(), p - origin, 0.0);
+        let n = self.lights.len() as f64;
+        self.lights
+            .iter()
+            .filter(|light| light.hit(&r, 1.0 - 1e-6, 1.0 + 1e-6).is_some())
+            .map(|light| 1.0 / (n * light.area()))
+            .sum()
+    }
+}
+
+pub const NAMES: [&str; 23] = [
+    "random",
+    "bouncing",
+    "caustics",
+    "fog",
+    "clouds",
+    "cornell",
+    "shapes",
+    "instances",
+    "orbit",
+    "csg",
+    "sdf",
+    "metaballs",
+    "terrain",
+    "hair",
+    "metals",
+    "frosted",
+    "principled",
+    "clay",
+    "coated",
+    "rust",
+    "dispersion",
+    "iridescence",
+    "subsurface",
+];
+
+// Scenes that can be rendered as image sequences
+pub const ANIMATIONS: [&str; 1] = ["orbit"];
+
+pub fn load(name: &str, aspect_ratio: f64) -> Result<Scene, String> {
+    match name {
+        "random" | "bouncing" => {
+            let bouncing = name == "bouncing";
+            let camera = Camera::new(
+                Point3::new(13.0, 2.0, 3.0),
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+                20.0,
+                aspect_ratio,
+                0.1,
+                10.0,
+            )
+            .with_shutter(0.0, if bouncing { 1.0 } else { 0.0 });
+            Ok(Scene::new(
+                random_scene(bouncing),
+                Vec::new(),
+                Background::Sky,
+                camera,
+            ))
+        }
+        "caustics" => Ok(caustics_scene(aspect_ratio)),
+        "fog" => Ok(fog_scene(aspect_ratio)),
+        "clouds" => Ok(clouds_scene(aspect_ratio)),
+        "cornell" => Ok(cornell_box(aspect_ratio)),
+        "shapes" => Ok(shapes_scene(aspect_ratio)),
+        "instances" => Ok(instances_scene(aspect_ratio)),
+        "orbit" => Ok(orbit_animation().frame(0.0, aspect_ratio)),
+        "csg" => Ok(csg_scene(aspect_ratio)),
+        "sdf" => Ok(sdf_scene(aspect_ratio)),
+        "metaballs" => Ok(metaballs_scene(aspect_ratio)),
+        "terrain" => Ok(terrain_scene(aspect_ratio)),
+        "hair" => Ok(hair_scene(aspect_ratio)),
+        "metals" => Ok(metals_scene(aspect_ratio)),
+        "frosted" => Ok(frosted_scene(aspect_ratio)),
+        "principled" => Ok(principled_scene(aspect_ratio)),
+        "clay" => Ok(clay_scene(aspect_ratio)),
+        "coated" => Ok(coated_scene(aspect_ratio)),
+        "rust" => Ok(rust_scene(aspect_ratio)),
+        "dispersion" => Ok(dispersion_scene(aspect_ratio)),
+        "iridescence" => Ok(iridescence_scene(aspect_ratio)),
+        "subsurface" => Ok(subsurface_scene(aspect_ratio)),
+        _ => Err(format!(
+            "unknown scene '{}', expected one of: {}",
+            name,
+            NAMES.join(", ")
+        )),
+    }
+}
+
+pub fn load_animation(name: &str) -> Result<Animation, String> {
+    match name {
+        "orbit" => Ok(orbit_animation()),
+        _ => Err(format!(
+            "scene '{}' is not animated, expected one of: {}",
+            name,
+            ANIMATIONS.join(", ")
+        )),
+    }
+}
+
+// With bouncing, the small diffuse spheres move up during the shutter interval [0, 1]
+fn random_scene(bouncing: bool) -> HittableList {
+    let world = HittableList::new();
+
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+
+    let world = world.push(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        ground_material,
+    ));
+
+    let world = (-11..11).zip(-11..11).fold(world, |world, (a, b)| {
+        let mut rng = rand::thread_rng();
+        let choose_mat = rng.gen_range(0.0..1.0);
+        let center = Point3::new(
+            a as f64 + 0.9 * rng.gen_range(0.0..1.0),
+            0.2,
+            b as f64 + 0.9 * rng.gen_range(0.0..1.0),
+        );
+
+        if (&center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+            let sphere_material: Box<dyn Material + Sync + Send + 'static> = if choose_mat < 0.8 {
+                // diffuse
+                let albedo = Color::vec3_random_range(&mut rng, 0.0..1.0);
+                Box::new(Lambertian::new(albedo))
+            } else if choose_mat < 0.95 {
+                // metal
+                let albedo = Color::vec3_random_range(&mut rng, 0.0..0.5);
+                let fuzz = rng.gen_range(0.0..0.5);
+                Box::new(Metal::new(albedo, fuzz))
+            } else {
+                // glass
+                Box::new(Dielectrics::new(1.0))
+            };
+            if bouncing && choose_mat < 0.8 {
+                let center2 = &center + Vec3::new(0.0, rng.gen_range(0.0..0.5), 0.0);
+                world.push(MovingSphere::new(
+                    (center, center2),
+                    (0.0, 1.0),
+                    0.2,
+                    Arc::new(sphere_material),
+                ))
+            } else {
+                world.push(Sphere::new(center, 0.2, Arc::new(sphere_material)))
+            }
+        } else {
+            world
+        }
+    });
+
+    let material1: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+
+    let material2: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))));
+
+    let material3: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
+
+    world
+        .push(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1))
+        .push(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2))
+        .push(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3))
+}
+
+// Glass spheres lit by a small light in a dark environment
+fn caustics_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let glass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+    let diffuse: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))));
+    let light: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(40.0, 40.0, 40.0))));
+
+    let light_center = Point3::new(-3.0, 6.0, -2.0);
+    let light_radius = 0.5;
+
+    let world = HittableList::new()
+        .push(Sphere::new(
+            Point3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            ground_material,
+        ))
+        .push(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, glass.clone()))
+        .push(Sphere::new(Point3::new(2.5, 0.5, 1.5), 0.5, glass))
+        .push(Sphere::new(Point3::new(-2.5, 0.7, 1.0), 0.7, diffuse))
+        .push(Sphere::new(
+            light_center.clone(),
+            light_radius,
+            light.clone(),
+        ));
+
+    let camera = Camera::new(
+        Point3::new(8.0, 4.0, 6.0),
+        Point3::new(0.0, 0.8, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        30.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(
+        world,
+        vec![Box::new(Sphere::new(light_center, light_radius, light))],
+        Background::Solid(Color::new(0.01, 0.01, 0.01)),
+        camera,
+    )
+}
+
+// Smoke spheres in a thin mist that also contains the camera
+fn fog_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.48, 0.83, 0.53))));
+    let glass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+    let metal: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.8, 0.9), 0.0)));
+
+    let world = HittableList::new()
+        .push(Sphere::new(
+            Point3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            ground_material,
+        ))
+        .push(ConstantMedium::new(
+            Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, glass.clone()),
+            2.0,
+            Color::new(1.0, 1.0, 1.0),
+        ))
+        .push(ConstantMedium::new(
+            Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, glass.clone()),
+            2.0,
+            Color::new(0.05, 0.05, 0.05),
+        ))
+        // Glass shell filled with blue smoke
+        .push(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, glass.clone()))
+        .push(ConstantMedium::new(
+            Sphere::new(Point3::new(0.0, 1.0, 0.0), 0.99, glass.clone()),
+            1.0,
+            Color::new(0.2, 0.4, 0.9),
+        ))
+        .push(Sphere::new(Point3::new(1.5, 0.5, 2.5), 0.5, metal))
+        .push(ConstantMedium::new(
+            Sphere::new(Point3::new(0.0, 0.0, 0.0), 50.0, glass),
+            0.02,
+            Color::new(1.0, 1.0, 1.0),
+        ));
+
+    let camera = Camera::new(
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.5, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        25.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// A procedural cloud next to a smoke plume stored in a voxel grid
+fn clouds_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let boundary: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.0, 0.0, 0.0))));
+
+    let cloud = HeterogeneousMedium::new(
+        Sphere::new(Point3::new(2.0, 3.5, 0.0), 2.5, boundary.clone()),
+        NoiseDensity::new(0.8, 5, 6.0, 0.35),
+        Color::new(0.0, 0.0, 0.0),
+        Color::new(1.0, 1.0, 1.0),
+        0.6,
+    );
+
+    // Column that widens and thins out with height. Its boundary rests just
+    // above the ground, which must not be inside the boundary.
+    let plume_bounds = Aabb::new(Point3::new(-5.5, 0.01, -1.5), Point3::new(-2.5, 5.0, 1.5));
+    let plume_density = VoxelGrid::from_fn(plume_bounds, 32, 48, 32, |x, y, z| {
+        let r = ((x - 0.5).powi(2) + (z - 0.5).powi(2)).sqrt();
+        let width = 0.08 + 0.4 * y;
+        3.0 * (1.0 - r / width).max(0.0) * (1.0 - y)
+    });
+    let plume = HeterogeneousMedium::new(
+        Sphere::new(Point3::new(-4.0, 2.91, 0.0), 2.9, boundary),
+        plume_density,
+        Color::new(0.1, 0.5, 1.2),
+        Color::new(0.9, 0.6, 0.3),
+        0.2,
+    );
+
+    let world = HittableList::new()
+        .push(Sphere::new(
+            Point3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            ground_material,
+        ))
+        .push(cloud)
+        .push(plume);
+
+    let camera = Camera::new(
+        Point3::new(0.0, 2.5, 16.0),
+        Point3::new(-1.0, 2.5, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        35.0,
+        aspect_ratio,
+        0.0,
+        16.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// The classic Cornell box, lit by a quad light facing down from the ceiling
+fn cornell_box(aspect_ratio: f64) -> Scene {
+    let red: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.65, 0.05, 0.05))));
+    let white: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.73, 0.73, 0.73))));
+    let green: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.12, 0.45, 0.15))));
+    let light: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0))));
+
+    let light_quad = || {
+        Quad::new(
+            Point3::new(343.0, 554.0, 332.0),
+            Vec3::new(-130.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -105.0),
+            light.clone(),
+        )
+    };
+
+    let world = HittableList::new()
+        .push(Quad::yz_rect((0.0, 555.0), (0.0, 555.0), 555.0, green))
+        .push(Quad::yz_rect((0.0, 555.0), (0.0, 555.0), 0.0, red))
+        .push(light_quad())
+        .push(Quad::xz_rect(
+            (0.0, 555.0),
+            (0.0, 555.0),
+            0.0,
+            white.clone(),
+        ))
+        .push(Quad::xz_rect(
+            (0.0, 555.0),
+            (0.0, 555.0),
+            555.0,
+            white.clone(),
+        ))
+        .push(Quad::xy_rect(
+            (0.0, 555.0),
+            (0.0, 555.0),
+            555.0,
+            white.clone(),
+        ))
+        .push(Instance::new(
+            Arc::new(BoxShape::new(
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(165.0, 330.0, 165.0),
+                white.clone(),
+            )),
+            Transform::identity()
+                .rotate_y(15.0)
+                .translate(Vec3::new(265.0, 0.0, 295.0)),
+        ))
+        .push(Instance::new(
+            Arc::new(BoxShape::new(
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(165.0, 165.0, 165.0),
+                white,
+            )),
+            Transform::identity()
+                .rotate_y(-18.0)
+                .translate(Vec3::new(130.0, 0.0, 65.0)),
+        ));
+
+    let camera = Camera::new(
+        Point3::new(278.0, 278.0, -800.0),
+        Point3::new(278.0, 278.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(
+        world,
+        vec![Box::new(light_quad())],
+        Background::Solid(Color::new(0.0, 0.0, 0.0)),
+        camera,
+    )
+}
+
+// Analytic primitives on an infinite ground plane, under a disk light
+fn shapes_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let glass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+    let copper: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.5, 0.3), 0.1)));
+    let blue: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.1, 0.2, 0.6))));
+    let light: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(8.0, 8.0, 8.0))));
+
+    let light_disk = || {
+        Disk::new(
+            Point3::new(0.0, 6.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            1.5,
+            light.clone(),
+        )
+    };
+
+    let world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(Cylinder::new(
+            Point3::new(-3.0, 0.0, 0.0),
+            0.8,
+            2.0,
+            copper.clone(),
+        ))
+        .push(Cone::new(
+            Point3::new(3.0, 0.0, 0.0),
+            1.0,
+            2.2,
+            blue.clone(),
+        ))
+        .push(Torus::new(Point3::new(0.0, 0.5, 0.0), 1.2, 0.5, glass))
+        .push(Disk::new(
+            Point3::new(0.0, 0.001, 2.5),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.8,
+            copper,
+        ))
+        .push(light_disk());
+
+    let camera = Camera::new(
+        Point3::new(0.0, 4.0, 10.0),
+        Point3::new(0.0, 0.8, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        35.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(
+        world,
+        vec![Box::new(light_disk())],
+        Background::Solid(Color::new(0.2, 0.25, 0.35)),
+        camera,
+    )
+}
+
+// A field of randomly rotated and stretched copies of two shared meshes of
+// spheres, in a two-level acceleration structure
+fn instances_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let gold: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.2)));
+    let red: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.7, 0.1, 0.1))));
+
+    // A necklace of beads around a torus, and a helix
+    let necklace = (0..48).fold(
+        HittableList::new().push(Torus::new(
+            Point3::new(0.0, 0.0, 0.0),
+            0.3,
+            0.02,
+            gold.clone(),
+        )),
+        |list, i| {
+            let phi = 2.0 * PI * i as f64 / 48.0;
+            list.push(Sphere::new(
+                Point3::new(0.3 * phi.cos(), 0.0, 0.3 * phi.sin()),
+                0.035,
+                gold.clone(),
+            ))
+        },
+    );
+    let helix = (0..64).fold(HittableList::new(), |list, i| {
+        let phi = 4.0 * PI * i as f64 / 64.0;
+        list.push(Sphere::new(
+            Point3::new(
+                0.2 * phi.cos(),
+                -0.3 + 0.6 * i as f64 / 64.0,
+                0.2 * phi.sin(),
+            ),
+            0.04,
+            red.clone(),
+        ))
+    });
+    let meshes = [
+        Arc::new(Blas::new(necklace, 0.0, 0.0)),
+        Arc::new(Blas::new(helix, 0.0, 0.0)),
+    ];
+
+    let mut rng = rand::thread_rng();
+    let instances = (-10i32..10)
+        .flat_map(|a| (-10..10).map(move |b| (a, b)))
+        .map(|(a, b)| {
+            let mesh = meshes[(a + b).rem_euclid(2) as usize].clone();
+            let axis = Vec3::random_unit_vector(&mut rng);
+            let stretch = rng.gen_range(0.7..1.4);
+            let transform = Transform::identity()
+                .scale(Vec3::new(stretch, 1.0 / stretch, 1.0))
+                .rotate(axis, rng.gen_range(0.0..360.0))
+                .translate(Vec3::new(a as f64 * 0.8, 0.45, b as f64 * 0.8));
+            (mesh, transform)
+        })
+        .collect();
+    let tlas = Tlas::new(instances, 0.0, 0.0);
+    let memory_report = tlas.memory_report();
+
+    let world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(tlas);
+    let camera = Camera::new(
+        Point3::new(6.0, 4.0, 9.0),
+        Point3::new(0.0, 0.4, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        30.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera).with_memory_report(memory_report)
+}
+
+// Two seconds of a torus hopping around three spheres and a pulsing box,
+// seen by a camera circling the scene
+fn orbit_animation() -> Animation {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let glass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+    let brown: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))));
+    let steel: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
+    let gold: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.1)));
+    let blue: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.1, 0.2, 0.6))));
+    let lamp: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(8.0, 6.0, 3.0))));
+    let lamp_center = Point3::new(0.0, 4.0, 0.0);
+
+    let world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, glass))
+        .push(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, brown))
+        .push(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, steel))
+        .push(Sphere::new(lamp_center.clone(), 0.3, lamp.clone()));
+
+    // Hops between the spheres, spinning as it goes
+    let torus_track = TransformTrack::new()
+        .position(
+            Track::new(Interpolation::Spline)
+                .key(0.0, Vec3::new(-2.0, 0.4, 2.0))
+                .key(0.5, Vec3::new(0.0, 2.5, 2.5))
+                .key(1.0, Vec3::new(2.0, 0.4, 2.0))
+                .key(1.5, Vec3::new(0.0, 2.5, 1.5))
+                .key(2.0, Vec3::new(-2.0, 0.4, 2.0)),
+        )
+        .rotation(
+            Track::new(Interpolation::Linear)
+                .key(0.0, Vec3::new(90.0, 0.0, 0.0))
+                .key(2.0, Vec3::new(90.0, 720.0, 0.0)),
+        );
+    let box_track = TransformTrack::new()
+        .position(Track::constant(Vec3::new(0.0, 0.0, -3.0)))
+        .rotation(
+            Track::new(Interpolation::Linear)
+                .key(0.0, Vec3::new(0.0, 0.0, 0.0))
+                .key(2.0, Vec3::new(0.0, 180.0, 0.0)),
+        )
+        .scale(
+            Track::new(Interpolation::Spline)
+                .key(0.0, Vec3::new(1.0, 1.0, 1.0))
+                .key(1.0, Vec3::new(1.2, 2.0, 1.2))
+                .key(2.0, Vec3::new(1.0, 1.0, 1.0)),
+        );
+
+    // Half a turn around the scene
+    let lookfrom = (0..=4).fold(Track::new(Interpolation::Spline), |track, i| {
+        let angle = 0.25 * PI + 0.25 * PI * i as f64;
+        track.key(
+            0.5 * i as f64,
+            Point3::new(13.0 * angle.cos(), 2.5, 13.0 * angle.sin()),
+        )
+    });
+    let camera = CameraTrack::new(lookfrom, Track::constant(Point3::new(0.0, 0.8, 0.0)))
+        .vertical_fov(
+            Track::new(Interpolation::Spline)
+                .key(0.0, 25.0)
+                .key(1.0, 20.0)
+                .key(2.0, 25.0),
+        )
+        .aperture(Track::constant(0.1))
+        .focus_dist(Track::constant(13.0));
+
+    Animation::new(world, Background::Sky, camera)
+        .light(Arc::new(Sphere::new(lamp_center, 0.3, lamp)))
+        .animate(
+            Arc::new(Torus::new(Point3::new(0.0, 0.0, 0.0), 0.4, 0.15, gold)),
+            torus_track,
+        )
+        .animate(
+            Arc::new(BoxShape::new(
+                Point3::new(-0.5, 0.0, -0.5),
+                Point3::new(0.5, 1.0, 0.5),
+                blue,
+            )),
+            box_track,
+        )
+}
+
+// Solids carved with CSG: a lens, a drilled die and a bowl
+fn csg_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let glass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+    let red: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.7, 0.1, 0.1))));
+    let white: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.8, 0.8, 0.8))));
+    let gold: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.05)));
+
+    // Biconvex lens facing the camera, with a small sphere behind it
+    let lens = Csg::intersection(
+        Sphere::new(Point3::new(-3.0, 1.2, 1.2), 1.5, glass.clone()),
+        Sphere::new(Point3::new(-3.0, 1.2, -1.2), 1.5, glass),
+    );
+
+    // Rounded cube with a hole drilled along every axis
+    let drill = |axis: Vec3, degrees: f64| {
+        Instance::new(
+            Arc::new(Cylinder::new(
+                Point3::new(0.0, -1.5, 0.0),
+                0.5,
+                3.0,
+                white.clone(),
+            )),
+            Transform::identity().rotate(axis, degrees),
+        )
+    };
+    let holes = Csg::union(
+        Csg::union(
+            drill(Vec3::new(1.0, 0.0, 0.0), 0.0),
+            drill(Vec3::new(1.0, 0.0, 0.0), 90.0),
+        ),
+        drill(Vec3::new(0.0, 0.0, 1.0), 90.0),
+    );
+    let body = Csg::intersection(
+        BoxShape::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            red.clone(),
+        ),
+        Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.35, red),
+    );
+    let die = Instance::new(
+        Arc::new(Csg::difference(body, holes)),
+        Transform::identity()
+            .rotate_y(30.0)
+            .translate(Vec3::new(0.0, 1.0, 0.0)),
+    );
+
+    // Hollow sphere with its top cut off
+    let bowl = Csg::difference(
+        Csg::difference(
+            Sphere::new(Point3::new(3.0, 1.0, 0.0), 1.0, gold.clone()),
+            Sphere::new(Point3::new(3.0, 1.0, 0.0), 0.9, gold.clone()),
+        ),
+        BoxShape::new(
+            Point3::new(1.5, 1.3, -1.5),
+            Point3::new(4.5, 2.5, 1.5),
+            gold,
+        ),
+    );
+
+    let world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(lens)
+        .push(Sphere::new(
+            Point3::new(-3.0, 0.5, -2.5),
+            0.5,
+            Arc::new(Box::new(Lambertian::new(Color::new(0.1, 0.2, 0.6)))),
+        ))
+        .push(die)
+        .push(bowl);
+
+    let camera = Camera::new(
+        Point3::new(0.0, 4.0, 10.0),
+        Point3::new(0.0, 0.9, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        35.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Sphere traced distance fields next to an analytic sphere
+fn sdf_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let pink: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.8, 0.3, 0.4))));
+    let blue: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.1, 0.2, 0.6))));
+    let gold: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.1)));
+    let steel: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.7, 0.7, 0.75), 0.0)));
+    let glass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+
+    let bounds = |center: Point3, half_extent: f64| {
+        let e = Vec3::new(half_extent, half_extent, half_extent);
+        Aabb::new(&center - &e, &center + &e)
+    };
+
+    // Three spheres melted together
+    let blob = sdf::smooth_union(
+        sdf::smooth_union(
+            sdf::translate(sdf::sphere(0.6), Vec3::new(-4.5, 0.6, 0.0)),
+            sdf::translate(sdf::sphere(0.45), Vec3::new(-4.0, 1.4, 0.2)),
+            0.3,
+        ),
+        sdf::translate(sdf::sphere(0.35), Vec3::new(-5.0, 1.3, -0.2)),
+        0.3,
+    );
+
+    let twisted = sdf::translate(
+        sdf::twist(sdf::rounded_box(Vec3::new(0.5, 1.0, 0.5), 0.1), 1.2),
+        Vec3::new(-1.5, 1.0, 0.0),
+    );
+
+    // A 3x3x3 grid of beads cut out of an infinite repetition
+    let beads = sdf::translate(
+        sdf::intersection(
+            sdf::repeat(sdf::sphere(0.15), Vec3::new(0.5, 0.5, 0.5)),
+            sdf::rounded_box(Vec3::new(0.7, 0.7, 0.7), 0.0),
+        ),
+        Vec3::new(1.5, 0.8, 0.0),
+    );
+
+    let bumpy = sdf::displace(
+        sdf::translate(sdf::sphere(0.8), Vec3::new(4.5, 0.9, 0.0)),
+        |p: &Point3| 0.05 * (10.0 * p.x).sin() * (10.0 * p.y).sin() * (10.0 * p.z).sin(),
+    );
+
+    // A ring on a post, and a box hollowed out by a sphere
+    let ring = sdf::translate(
+        sdf::union(
+            sdf::translate(sdf::torus(0.6, 0.15), Vec3::new(0.0, 1.2, 0.0)),
+            sdf::capsule(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 1.2, 0.0), 0.1),
+        ),
+        Vec3::new(-3.0, 0.0, -3.0),
+    );
+    let carved = sdf::translate(
+        sdf::subtraction(
+            sdf::rounded_box(Vec3::new(0.6, 0.6, 0.6), 0.05),
+            sdf::sphere(0.75),
+        ),
+        Vec3::new(3.0, 0.6, -3.0),
+    );
+
+    let world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(SdfShape::new(
+            ring,
+            bounds(Point3::new(-3.0, 0.8, -3.0), 0.8),
+            gold.clone(),
+        ))
+        .push(SdfShape::new(
+            carved,
+            bounds(Point3::new(3.0, 0.6, -3.0), 0.65),
+            pink.clone(),
+        ))
+        .push(SdfShape::new(
+            blob,
+            bounds(Point3::new(-4.5, 1.0, 0.0), 1.1),
+            pink,
+        ))
+        .push(
+            SdfShape::new(twisted, bounds(Point3::new(-1.5, 1.0, 0.0), 1.1), blue)
+                .with_step_scale(0.6),
+        )
+        .push(SdfShape::new(
+            beads,
+            bounds(Point3::new(1.5, 0.8, 0.0), 0.75),
+            gold,
+        ))
+        .push(
+            SdfShape::new(bumpy, bounds(Point3::new(4.5, 0.9, 0.0), 0.9), steel)
+                .with_step_scale(0.5),
+        )
+        .push(Sphere::new(Point3::new(0.0, 0.5, 2.5), 0.5, glass));
+
+    let camera = Camera::new(
+        Point3::new(0.0, 3.0, 11.0),
+        Point3::new(0.0, 0.9, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        35.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Blobby molecules: water, methane and a ring with a negative blob in its middle
+fn metaballs_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let blue: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.2, 0.4, 0.8))));
+    let gold: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.2)));
+    let glass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Dielectrics::new(1.5)));
+
+    // Hydrogens 104.5 degrees apart
+    let half_angle = (104.5f64 / 2.0).to_radians();
+    let water = Metaballs::new(0.25, blue)
+        .blob(Point3::new(-3.5, 1.2, 0.0), 1.6, 1.0)
+        .blob(
+            Point3::new(
+                -3.5 + 0.9 * half_angle.sin(),
+                1.2 + 0.9 * half_angle.cos(),
+                0.0,
+            ),
+            1.1,
+            1.0,
+        )
+        .blob(
+            Point3::new(
+                -3.5 - 0.9 * half_angle.sin(),
+                1.2 + 0.9 * half_angle.cos(),
+                0.0,
+            ),
+            1.1,
+            1.0,
+        );
+
+    // Hydrogens on alternate corners of a cube around the carbon
+    let carbon = Point3::new(0.0, 1.3, 0.0);
+    let methane = [
+        (1.0, 1.0, 1.0),
+        (1.0, -1.0, -1.0),
+        (-1.0, 1.0, -1.0),
+        (-1.0, -1.0, 1.0),
+    ]
+    .iter()
+    .fold(
+        Metaballs::new(0.25, gold).blob(carbon.clone(), 1.6, 1.0),
+        |molecule, &(x, y, z)| {
+            let offset = 0.6 * Vec3::new(x, y, z);
+            molecule.blob(&carbon + &offset, 1.0, 1.0)
+        },
+    );
+
+    let ring = (0..6).fold(
+        Metaballs::new(0.25, glass).blob(Point3::new(3.5, 1.2, 0.0), 1.0, -1.0),
+        |molecule, k| {
+            let angle = k as f64 * PI / 3.0;
+            molecule.blob(
+                Point3::new(3.5 + angle.cos(), 1.2 + angle.sin(), 0.0),
+                1.0,
+                1.0,
+            )
+        },
+    );
+
+    let world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(water)
+        .push(methane)
+        .push(ring);
+
+    let camera = Camera::new(
+        Point3::new(0.0, 3.0, 12.0),
+        Point3::new(0.0, 1.2, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        35.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Noise landscape from a grayscale heightmap, colored by a texture that follows
+// the same image coordinates
+fn terrain_scene(aspect_ratio: f64) -> Scene {
+    let noise = Perlin::new();
+    // Rolling hills with some finer detail on top
+    let elevation = |u: f64, v: f64| {
+        let at = |scale: f64| noise.noise(&Point3::new(scale * u, 0.0, scale * v));
+        (0.45 + 0.5 * at(3.0) + 0.15 * at(9.0) + 0.05 * at(27.0)).clamp(0.0, 1.0)
+    };
+
+    let resolution = 256;
+    let heightmap = ImageTexture::from_fn(resolution, resolution, |u, v| {
+        let h = elevation(u, v);
+        Color::new(h, h, h)
+    });
+    let colors = ImageTexture::from_fn(resolution, resolution, |u, v| {
+        let h = elevation(u, v);
+        if h < 0.25 {
+            Color::new(0.6, 0.55, 0.4)
+        } else if h < 0.55 {
+            Color::new(0.2, 0.45, 0.15)
+        } else if h < 0.75 {
+            Color::new(0.4, 0.35, 0.3)
+        } else {
+            Color::new(0.9, 0.9, 0.92)
+        }
+    });
+
+    let terrain = Heightfield::from_image(
+        Aabb::new(Point3::new(-10.0, 0.0, -10.0), Point3::new(10.0, 3.0, 10.0)),
+        &heightmap,
+        Arc::new(Box::new(Lambertian::textured(colors))),
+    );
+    let world = HittableList::new().push(terrain).push(Sphere::new(
+        Point3::new(0.0, 4.5, 2.0),
+        1.0,
+        Arc::new(Box::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.0))),
+    ));
+
+    let camera = Camera::new(
+        Point3::new(0.0, 9.0, 18.0),
+        Point3::new(0.0, 1.5, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// A furry ball sitting in a patch of grass
+fn hair_scene(aspect_ratio: f64) -> Scene {
+    let mut rng = rand::thread_rng();
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.35, 0.25, 0.15))));
+    let skin: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.3, 0.2, 0.1))));
+    let fur: Arc<Box<dyn Material + Sync + Send + 'static>> = Arc::new(Box::new(
+        Hair::from_melanin(1.3, 0.8)
+            .with_roughness(0.25, 0.3)
+            .with_scale_angle(3.0),
+    ));
+    let grass: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.15, 0.4, 0.08))));
+
+    let center = Point3::new(0.0, 1.0, 0.0);
+    let radius = 1.0;
+    let mut world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(Sphere::new(center.clone(), radius, skin));
+
+    // Strands leave the skin along the normal and droop under their weight
+    for _ in 0..20_000 {
+        let normal = Vec3::random_unit_vector(&mut rng);
+        if normal.y < -0.6 {
+            continue;
+        }
+        let length = rng.gen_range(0.25..0.35);
+        let root = &center + &(radius * &normal);
+        let comb = 0.3 * Vec3::random_unit_vector(&mut rng);
+        let point = |s: f64, droop: f64| {
+            &(&root + &(s * length * &(&normal + &comb))) - &Vec3::new(0.0, droop * length, 0.0)
+        };
+        let strand = Curve::new(
+            [
+                root.clone(),
+                point(0.35, 0.0),
+                point(0.7, 0.15),
+                point(1.0, 0.4),
+            ],
+            (0.008, 0.001),
+            CurveMode::Ribbon,
+            fur.clone(),
+        );
+        world = strand
+            .split(4)
+            .fold(world, |world, segment| world.push(segment));
+    }
+
+    // Blades bending away from a random direction
+    for _ in 0..6000 {
+        let root = Point3::new(rng.gen_range(-5.0..5.0), 0.0, rng.gen_range(-4.0..3.0));
+        if (&root - &Point3::new(0.0, 0.0, 0.0)).length() < radius + 0.2 {
+            continue;
+        }
+        let height = rng.gen_range(0.3..0.7);
+        let angle = rng.gen_range(0.0..2.0 * PI);
+        let bend = rng.gen_range(0.05..0.3) * Vec3::new(angle.cos(), 0.0, angle.sin());
+        let blade = Curve::new(
+            [
+                root.clone(),
+                &root + &Vec3::new(0.0, 0.4 * height, 0.0),
+                &(&root + &Vec3::new(0.0, 0.8 * height, 0.0)) + &(0.5 * &bend),
+                &(&root + &Vec3::new(0.0, height, 0.0)) + &bend,
+            ],
+            (0.03, 0.0),
+            CurveMode::Tube,
+            grass.clone(),
+        );
+        world = blade
+            .split(4)
+            .fold(world, |world, segment| world.push(segment));
+    }
+
+    let camera = Camera::new(
+        Point3::new(0.0, 2.0, 6.0),
+        Point3::new(0.0, 0.9, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        35.0,
+        aspect_ratio,
+        0.0,
+        6.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Conductor presets, polished at the back and rough at the front
+fn metals_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let presets: [fn(f64) -> Conductor; 4] = [
+        Conductor::gold,
+        Conductor::copper,
+        Conductor::silver,
+        Conductor::aluminum,
+    ];
+
+    let world = presets.iter().enumerate().fold(
+        HittableList::new().push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        )),
+        |world, (i, preset)| {
+            let x = 2.2 * i as f64 - 3.3;
+            world
+                .push(Sphere::new(
+                    Point3::new(x, 1.0, -1.5),
+                    1.0,
+                    Arc::new(Box::new(preset(0.05))),
+                ))
+                .push(Sphere::new(
+                    Point3::new(x, 1.0, 1.5),
+                    1.0,
+                    Arc::new(Box::new(preset(0.4))),
+                ))
+        },
+    );
+
+    let camera = Camera::new(
+        Point3::new(0.0, 7.0, 11.0),
+        Point3::new(0.0, 0.5, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Glass spheres from polished to frosted in front of a checkered wall
+fn frosted_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let checker = ImageTexture::from_fn(16, 8, |u, v| {
+        if ((16.0 * u) as usize + (8.0 * v) as usize) % 2 == 0 {
+            Color::new(0.8, 0.8, 0.8)
+        } else {
+            Color::new(0.1, 0.1, 0.4)
+        }
+    });
+    let wall_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::textured(checker)));
+    let light: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0))));
+    let light_quad = || {
+        Quad::new(
+            Point3::new(3.0, 6.0, 3.0),
+            Vec3::new(-6.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -4.0),
+            light.clone(),
+        )
+    };
+
+    let world = [0.0, 0.1, 0.25, 0.5].iter().enumerate().fold(
+        HittableList::new()
+            .push(Plane::new(
+                Point3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                ground_material,
+            ))
+            .push(Quad::xy_rect((-8.0, 8.0), (0.0, 8.0), -3.0, wall_material))
+            .push(light_quad()),
+        |world, (i, &roughness)| {
+            world.push(Sphere::new(
+                Point3::new(2.4 * i as f64 - 3.6, 1.0, 0.0),
+                1.0,
+                Arc::new(Box::new(RoughDielectric::new(1.5, roughness))),
+            ))
+        },
+    );
+
+    let camera = Camera::new(
+        Point3::new(0.0, 2.5, 10.0),
+        Point3::new(0.0, 1.2, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(
+        world,
+        vec![Box::new(light_quad())],
+        Background::Solid(Color::new(0.1, 0.1, 0.12)),
+        camera,
+    )
+}
+
+// Rows of principled materials: plastic and metal from smooth to rough, then
+// clearcoat, sheen, glass, brushed metal and a textured sphere
+fn principled_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let stripes = |a: Color, b: Color| {
+        ImageTexture::from_fn(16, 1, move |u, _| {
+            if (16.0 * u) as usize % 2 == 0 {
+                a.clone()
+            } else {
+                b.clone()
+            }
+        })
+    };
+
+    let mut materials: Vec<Principled> = Vec::new();
+    for i in 0..5 {
+        let roughness = 0.25 * i as f64;
+        materials.push(Principled::new(Color::new(0.8, 0.1, 0.1)).with_roughness(roughness));
+    }
+    for i in 0..5 {
+        let roughness = 0.25 * i as f64;
+        materials.push(
+            Principled::new(Color::new(1.0, 0.77, 0.34))
+                .with_metallic(1.0)
+                .with_roughness(roughness),
+        );
+    }
+    materials.push(
+        Principled::new(Color::new(0.05, 0.1, 0.5))
+            .with_roughness(0.6)
+            .with_clearcoat(1.0, 0.9),
+    );
+    materials.push(
+        Principled::new(Color::new(0.3, 0.02, 0.05))
+            .with_roughness(1.0)
+            .with_sheen(1.0),
+    );
+    materials.push(
+        Principled::new(Color::new(0.8, 1.0, 0.85))
+            .with_roughness(0.1)
+            .with_transmission(1.0, 1.5),
+    );
+    materials.push(
+        Principled::new(Color::new(0.9, 0.9, 0.9))
+            .with_metallic(1.0)
+            .with_roughness(0.4)
+            .with_anisotropic(0.9),
+    );
+    materials.push(
+        Principled::textured(stripes(
+            Color::new(1.0, 0.77, 0.34),
+            Color::new(0.9, 0.9, 0.85),
+        ))
+        .with_metallic_texture(stripes(
+            Color::new(1.0, 1.0, 1.0),
+            Color::new(0.0, 0.0, 0.0),
+        ))
+        .with_roughness_texture(stripes(
+            Color::new(0.2, 0.2, 0.2),
+            Color::new(0.7, 0.7, 0.7),
+        ))
+        .with_specular(0.5, 0.5),
+    );
+
+    let world = materials.into_iter().enumerate().fold(
+        HittableList::new().push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        )),
+        |world, (i, material)| {
+            let x = 2.0 * (i % 5) as f64 - 4.0;
+            let z = 2.5 * (i / 5) as f64 - 2.5;
+            world.push(Sphere::new(
+                Point3::new(x, 0.8, z),
+                0.8,
+                Arc::new(Box::new(material)),
+            ))
+        },
+    );
+
+    let camera = Camera::new(
+        Point3::new(0.0, 7.0, 13.0),
+        Point3::new(0.0, 0.5, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        35.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Clay spheres, Lambertian and then Oren-Nayar of increasing roughness, on a
+// rough checkered floor, lit from behind the camera where the difference shows
+fn clay_scene(aspect_ratio: f64) -> Scene {
+    let clay = Color::new(0.7, 0.35, 0.2);
+    let checker = ImageTexture::from_fn(256, 256, |u, v| {
+        if ((16.0 * u) as usize + (16.0 * v) as usize) % 2 == 0 {
+            Color::new(0.7, 0.7, 0.65)
+        } else {
+            Color::new(0.3, 0.3, 0.35)
+        }
+    });
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(OrenNayar::textured(checker, 30.0)));
+    let light: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0))));
+    let light_quad = || {
+        Quad::new(
+            Point3::new(3.0, 2.0, 12.0),
+            Vec3::new(-6.0, 0.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+            light.clone(),
+        )
+    };
+
+    let materials: Vec<Arc<Box<dyn Material + Sync + Send + 'static>>> = vec![
+        Arc::new(Box::new(Lambertian::new(clay.clone()))),
+        Arc::new(Box::new(OrenNayar::new(clay.clone(), 20.0))),
+        Arc::new(Box::new(OrenNayar::new(clay.clone(), 45.0))),
+        Arc::new(Box::new(OrenNayar::new(clay, 90.0))),
+    ];
+    let world = materials.into_iter().enumerate().fold(
+        HittableList::new()
+            .push(Quad::xz_rect(
+                (-20.0, 20.0),
+                (-20.0, 20.0),
+                0.0,
+                ground_material,
+            ))
+            .push(light_quad()),
+        |world, (i, material)| {
+            world.push(Sphere::new(
+                Point3::new(2.4 * i as f64 - 3.6, 1.0, 0.0),
+                1.0,
+                material,
+            ))
+        },
+    );
+
+    let camera = Camera::new(
+        Point3::new(0.0, 2.5, 10.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(
+        world,
+        vec![Box::new(light_quad())],
+        Background::Solid(Color::new(0.05, 0.05, 0.06)),
+        camera,
+    )
+}
+
+// Coatings: car paint over a diffuse base, red candy over metal, varnished
+// wood and a satin finish with a rough coating
+fn coated_scene(aspect_ratio: f64) -> Scene {
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let wood = ImageTexture::from_fn(512, 256, |u, v| {
+        let ring = (24.0 * u + 0.8 * (2.0 * PI * 3.0 * v).sin()).fract();
+        let t = (ring * 2.0 - 1.0).abs().powi(3);
+        (1.0 - t) * &Color::new(0.75, 0.5, 0.3) + t * &Color::new(0.4, 0.22, 0.1)
+    });
+
+    let bases: [Arc<Box<dyn Material + Sync + Send + 'static>>; 4] = [
+        Arc::new(Box::new(Lambertian::new(Color::new(0.6, 0.02, 0.02)))),
+        Arc::new(Box::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.2))),
+        Arc::new(Box::new(Lambertian::textured(wood))),
+        Arc::new(Box::new(Lambertian::new(Color::new(0.1, 0.3, 0.6)))),
+    ];
+    let [paint, metal, wood, satin] = bases;
+    let materials = vec![
+        Coated::new(paint, 1.5),
+        Coated::new(metal, 1.5).with_absorption(Color::new(0.1, 2.0, 3.0), 0.5),
+        Coated::new(wood, 1.5).with_absorption(Color::new(0.2, 0.6, 1.4), 0.3),
+        Coated::new(satin, 1.5).with_roughness(0.3),
+    ];
+
+    let world = materials.into_iter().enumerate().fold(
+        HittableList::new().push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        )),
+        |world, (i, material)| {
+            world.push(Sphere::new(
+                Point3::new(2.4 * i as f64 - 3.6, 1.0, 0.0),
+                1.0,
+                Arc::new(Box::new(material)),
+            ))
+        },
+    );
+
+    let camera = Camera::new(
+        Point3::new(0.0, 3.0, 10.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Materials painted over others by noise masks: rust on steel, patina on
+// copper and dirt on the floor, next to an even mix of diffuse and mirror
+fn rust_scene(aspect_ratio: f64) -> Scene {
+    let noise = Perlin::new();
+    // Patches where turbulence on the unit sphere, at the point of the
+    // sphere's (u, v), exceeds a threshold
+    let patches = |scale: f64, threshold: f64| {
+        ImageTexture::from_fn(512, 256, |u, v| {
+            let (theta, phi) = (PI * v, 2.0 * PI * u - PI);
+            let p = Point3::new(
+                theta.sin() * phi.cos(),
+                -theta.cos(),
+                -theta.sin() * phi.sin(),
+            );
+            let t = noise.turb(&(scale * &p), 7);
+            let m = ((t - threshold) / 0.15).clamp(0.0, 1.0);
+            Color::new(m, m, m)
+        })
+    };
+    // Roughly the complex index of iron
+    let steel: Arc<Box<dyn Material + Sync + Send + 'static>> = Arc::new(Box::new(Conductor::new(
+        Color::new(2.9, 2.9, 2.6),
+        Color::new(3.1, 3.0, 2.9),
+        0.2,
+    )));
+    let copper: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Conductor::copper(0.15)));
+    let rust: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(OrenNayar::new(Color::new(0.45, 0.16, 0.05), 40.0)));
+    let patina: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(OrenNayar::new(Color::new(0.25, 0.55, 0.45), 30.0)));
+    let tiles: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.7, 0.7, 0.68))));
+    let dirt: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.25, 0.18, 0.1))));
+    let white: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.8, 0.8, 0.8))));
+    let mirror: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.0)));
+
+    let materials = vec![
+        MixMaterial::masked(steel, rust, patches(3.0, 0.3)),
+        MixMaterial::masked(copper, patina, patches(5.0, 0.4)),
+        MixMaterial::new(white, mirror, 0.5),
+    ];
+    let dirty_floor = MixMaterial::masked(tiles, dirt, patches(20.0, 0.45));
+
+    let world = materials.into_iter().enumerate().fold(
+        HittableList::new().push(Quad::xz_rect(
+            (-10.0, 10.0),
+            (-10.0, 10.0),
+            0.0,
+            Arc::new(Box::new(dirty_floor)),
+        )),
+        |world, (i, material)| {
+            world.push(Sphere::new(
+                Point3::new(2.6 * i as f64 - 2.6, 1.0, 0.0),
+                1.0,
+                Arc::new(Box::new(material)),
+            ))
+        },
+    );
+
+    let camera = Camera::new(
+        Point3::new(0.0, 3.0, 9.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Dispersive glass, diamond and an exaggerated Cauchy glass under a strip
+// light, casting colored caustics in front of a checkered wall. Meant for
+// --spectral, in RGB they refract every color alike.
+fn dispersion_scene(aspect_ratio: f64) -> Scene {
+    let checker = || {
+        ImageTexture::from_fn(256, 256, |u, v| {
+            if ((16.0 * u) as usize + (16.0 * v) as usize) % 2 == 0 {
+                Color::new(0.75, 0.75, 0.75)
+            } else {
+                Color::new(0.15, 0.15, 0.15)
+            }
+        })
+    };
+    let floor_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.7, 0.7, 0.7))));
+    let wall_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::textured(checker())));
+    let light: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(12.0, 12.0, 12.0))));
+    let light_quad = || {
+        Quad::new(
+            Point3::new(6.0, 6.0, -2.0),
+            Vec3::new(-12.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -2.0),
+            light.clone(),
+        )
+    };
+
+    let glasses = vec![
+        Dielectrics::bk7(),
+        Dielectrics::dense_flint(),
+        Dielectrics::diamond(),
+        Dielectrics::cauchy(1.5, 0.05),
+    ];
+    let world = glasses.into_iter().enumerate().fold(
+        HittableList::new()
+            .push(Quad::xz_rect(
+                (-20.0, 20.0),
+                (-6.0, 20.0),
+                0.0,
+                floor_material,
+            ))
+            .push(Quad::xy_rect(
+                (-20.0, 20.0),
+                (0.0, 20.0),
+                -6.0,
+                wall_material,
+            ))
+            .push(light_quad()),
+        |world, (i, glass)| {
+            world.push(Sphere::new(
+                Point3::new(2.4 * i as f64 - 3.6, 1.0, 0.0),
+                1.0,
+                Arc::new(Box::new(glass)),
+            ))
+        },
+    );
+
+    let camera = Camera::new(
+        Point3::new(0.0, 3.0, 11.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(
+        world,
+        vec![Box::new(light_quad())],
+        Background::Solid(Color::new(0.02, 0.02, 0.02)),
+        camera,
+    )
+}
+
+// Thin films: soap bubbles of swirling thickness, steel under an oxide layer
+// that thickens towards its base, and an oil slick on a puddle
+fn iridescence_scene(aspect_ratio: f64) -> Scene {
+    let noise = Perlin::new();
+    // Film thickness in nm, from min to max by turbulence at (u, v)
+    let swirls = |scale: f64, min: f64, max: f64| {
+        ImageTexture::from_fn(512, 256, |u, v| {
+            let t = noise.turb(&Point3::new(scale * u, scale * v, 0.0), 5);
+            let nm = min + (max - min) * (0.5 * t).min(1.0);
+            Color::new(nm, nm, nm)
+        })
+    };
+    let oxide = ImageTexture::from_fn(1, 256, |_, v| {
+        let nm = 50.0 + 300.0 * (1.0 - v);
+        Color::new(nm, nm, nm)
+    });
+
+    let ground_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.3, 0.3, 0.3))));
+    let puddle_bottom: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.03, 0.03, 0.03))));
+    let oil: Arc<Box<dyn Material + Sync + Send + 'static>> = Arc::new(Box::new(
+        ThinFilm::dielectric(1.33, 1.47, 0.0).with_thickness_texture(swirls(6.0, 150.0, 700.0)),
+    ));
+    let bubble = |scale: f64| -> Arc<Box<dyn Material + Sync + Send + 'static>> {
+        Arc::new(Box::new(
+            ThinFilm::dielectric(1.0, 1.33, 0.0)
+                .with_thickness_texture(swirls(scale, 200.0, 900.0)),
+        ))
+    };
+    let tempered_steel: Arc<Box<dyn Material + Sync + Send + 'static>> = Arc::new(Box::new(
+        ThinFilm::conductor(
+            Color::new(2.9, 2.9, 2.6),
+            Color::new(3.1, 3.0, 2.9),
+            2.4,
+            0.0,
+        )
+        .with_thickness_texture(oxide),
+    ));
+
+    let world = HittableList::new()
+        .push(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground_material,
+        ))
+        .push(Quad::xz_rect((-6.0, 6.0), (-2.0, 4.0), 0.01, puddle_bottom))
+        .push(Quad::xz_rect((-6.0, 6.0), (-2.0, 4.0), 0.02, oil))
+        .push(Sphere::new(Point3::new(-2.2, 1.6, 0.0), 1.2, bubble(4.0)))
+        .push(Sphere::new(Point3::new(-0.4, 2.6, -1.5), 0.6, bubble(6.0)))
+        .push(Sphere::new(Point3::new(2.2, 1.0, 0.0), 1.0, tempered_steel));
+
+    let camera = Camera::new(
+        Point3::new(0.0, 3.0, 10.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(world, Vec::new(), Background::Sky, camera)
+}
+
+// Translucent marble, skin, jade and wax lit from behind, next to a plain
+// diffuse sphere of the wax color
+fn subsurface_scene(aspect_ratio: f64) -> Scene {
+    let floor_material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let diffuse: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(Lambertian::new(Color::new(0.8, 0.65, 0.4))));
+    let light: Arc<Box<dyn Material + Sync + Send + 'static>> =
+        Arc::new(Box::new(DiffuseLight::new(Color::new(12.0, 12.0, 12.0))));
+    let light_quad = || {
+        Quad::new(
+            Point3::new(6.0, 6.0, -2.0),
+            Vec3::new(-12.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -2.0),
+            light.clone(),
+        )
+    };
+    // Ball of radius 1 at x
+    let ball = |x: f64| Sphere::new(Point3::new(x, 1.0, 0.0), 1.0, diffuse.clone());
+
+    let world = HittableList::new()
+        .push(Quad::xz_rect(
+            (-20.0, 20.0),
+            (-6.0, 20.0),
+            0.0,
+            floor_material,
+        ))
+        .push(light_quad())
+        .push(Subsurface::new(
+            ball(-4.4),
+            Color::new(0.999, 0.998, 0.995),
+            Color::new(0.3, 0.3, 0.3),
+            1.5,
+        ))
+        .push(
+            Subsurface::new(
+                ball(-2.2),
+                Color::new(0.99, 0.9, 0.8),
+                Color::new(0.6, 0.3, 0.2),
+                1.4,
+            )
+            .with_anisotropy(0.8),
+        )
+        .push(Subsurface::new(
+            ball(0.0),
+            Color::new(0.85, 0.98, 0.9),
+            Color::new(0.5, 0.3, 0.5),
+            1.6,
+        ))
+        .push(Subsurface::new(
+            ball(2.2),
+            Color::new(0.99, 0.93, 0.75),
+            Color::new(0.4, 0.35, 0.3),
+            1.45,
+        ))
+        .push(ball(4.4));
+
+    let camera = Camera::new(
+        Point3::new(0.0, 3.0, 11.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        40.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    );
+
+    Scene::new(
+        world,
+        vec![Box::new(light_quad())],
+        Background::Solid(Color::new(0.02, 0.02, 0.02)),
+        camera,
+    )
+}
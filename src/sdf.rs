@@ -0,0 +1,100 @@
+// Signed distance functions: negative inside, positive outside, and never
+// above the true distance to the surface. Primitives are centered at the
+// origin; operators combine and deform them.
+
+use crate::vec3::{Point3, Vec3};
+
+pub trait Sdf: Fn(&Point3) -> f64 + Sync + Send + 'static {}
+
+impl<F: Fn(&Point3) -> f64 + Sync + Send + 'static> Sdf for F {}
+
+// Primitives
+
+pub fn sphere(radius: f64) -> impl Sdf {
+    move |p: &Point3| p.length() - radius
+}
+
+// Box of half_extent, with edges rounded by radius
+pub fn rounded_box(half_extent: Vec3, radius: f64) -> impl Sdf {
+    move |p: &Point3| {
+        let q = Vec3::new(
+            p.x.abs() - half_extent.x + radius,
+            p.y.abs() - half_extent.y + radius,
+            p.z.abs() - half_extent.z + radius,
+        );
+        let outside = Vec3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+        outside + inside - radius
+    }
+}
+
+// Around the Y axis
+pub fn torus(major_radius: f64, minor_radius: f64) -> impl Sdf {
+    move |p: &Point3| {
+        let ring = (p.x * p.x + p.z * p.z).sqrt() - major_radius;
+        (ring * ring + p.y * p.y).sqrt() - minor_radius
+    }
+}
+
+// Segment from a to b, thickened by radius
+pub fn capsule(a: Point3, b: Point3, radius: f64) -> impl Sdf {
+    move |p: &Point3| {
+        let pa = p - &a;
+        let ba = &b - &a;
+        let h = (pa.dot(&ba) / ba.norm()).clamp(0.0, 1.0);
+        (pa - h * ba).length() - radius
+    }
+}
+
+// Operators
+
+pub fn translate(f: impl Sdf, offset: Vec3) -> impl Sdf {
+    move |p: &Point3| f(&(p - &offset))
+}
+
+pub fn union(a: impl Sdf, b: impl Sdf) -> impl Sdf {
+    move |p: &Point3| a(p).min(b(p))
+}
+
+pub fn subtraction(a: impl Sdf, b: impl Sdf) -> impl Sdf {
+    move |p: &Point3| a(p).max(-b(p))
+}
+
+pub fn intersection(a: impl Sdf, b: impl Sdf) -> impl Sdf {
+    move |p: &Point3| a(p).max(b(p))
+}
+
+// Union blended over a distance of about k
+pub fn smooth_union(a: impl Sdf, b: impl Sdf, k: f64) -> impl Sdf {
+    move |p: &Point3| {
+        let (da, db) = (a(p), b(p));
+        let h = (0.5 + 0.5 * (db - da) / k).clamp(0.0, 1.0);
+        db + (da - db) * h - k * h * (1.0 - h)
+    }
+}
+
+// Infinite copies of f, one per cell of size period. Bound it with the shape's box.
+pub fn repeat(f: impl Sdf, period: Vec3) -> impl Sdf {
+    move |p: &Point3| {
+        let cell = |x: f64, size: f64| x - size * (x / size).round();
+        f(&Point3::new(
+            cell(p.x, period.x),
+            cell(p.y, period.y),
+            cell(p.z, period.z),
+        ))
+    }
+}
+
+// Rotates slices of f around the Y axis by rate radians per unit of height.
+// Distances are stretched, so the shape needs a step scale below one.
+pub fn twist(f: impl Sdf, rate: f64) -> impl Sdf {
+    move |p: &Point3| {
+        let (s, c) = (rate * p.y).sin_cos();
+        f(&Point3::new(c * p.x - s * p.z, p.y, s * p.x + c * p.z))
+    }
+}
+
+// Adds the offset g to the surface of f. Steep offsets need a step scale below one.
+pub fn displace(f: impl Sdf, g: impl Sdf) -> impl Sdf {
+    move |p: &Point3| f(p) + g(p)
+}
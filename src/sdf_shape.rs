@@ -0,0 +1,159 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::sdf::Sdf;
+use crate::sphere::get_sphere_uv;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::sync::Arc;
+
+const MAX_STEPS: usize = 512;
+// Distance at which the surface counts as reached
+const EPSILON: f64 = 1e-4;
+
+// Surface of a signed distance function, found by sphere tracing inside bounds.
+// u and v follow the normal, like on a sphere.
+pub struct SdfShape {
+    sdf: Box<dyn Fn(&Point3) -> f64 + Sync + Send + 'static>,
+    bounds: Aabb,
+    // Fraction of the distance bound taken per step, for deformed functions
+    step_scale: f64,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl SdfShape {
+    pub fn new(
+        sdf: impl Sdf,
+        bounds: Aabb,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Self {
+        SdfShape {
+            sdf: Box::new(sdf),
+            bounds,
+            step_scale: 1.0,
+            mat_ptr,
+        }
+    }
+
+    pub fn with_step_scale(mut self, step_scale: f64) -> Self {
+        self.step_scale = step_scale;
+        self
+    }
+
+    // Gradient by central differences
+    fn normal(&self, p: &Point3) -> Vec3 {
+        let h = EPSILON;
+        let d = |dx: f64, dy: f64, dz: f64| {
+            (self.sdf)(&Point3::new(p.x + dx, p.y + dy, p.z + dz))
+                - (self.sdf)(&Point3::new(p.x - dx, p.y - dy, p.z - dz))
+        };
+        Vec3::new(d(h, 0.0, 0.0), d(0.0, h, 0.0), d(0.0, 0.0, h)).normalize()
+    }
+}
+
+impl Hittable for SdfShape {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t0, t1) = self.bounds.interval(r, t_min, t_max)?;
+        let length = r.direction().length();
+
+        // March on the side of the surface the ray starts on. A ray leaving the
+        // surface, e.g. after a bounce, first steps out of the epsilon band.
+        let mut t = t0;
+        let mut distance = (self.sdf)(&r.at(t));
+        let mut escape = 0;
+        while distance.abs() < EPSILON && escape < 16 {
+            t += EPSILON / length;
+            distance = (self.sdf)(&r.at(t));
+            escape += 1;
+        }
+        let side = distance.signum();
+
+        for _ in 0..MAX_STEPS {
+            if t > t1 {
+                return None;
+            }
+            let distance = side * (self.sdf)(&r.at(t));
+            if distance < EPSILON {
+                let p = r.at(t);
+                let outward_normal = self.normal(&p);
+                let uv = get_sphere_uv(&outward_normal);
+                return Some(HitRecord::from_face_normal(
+                    r,
+                    p,
+                    &outward_normal,
+                    t,
+                    uv,
+                    self.mat_ptr.clone(),
+                ));
+            }
+            t += self.step_scale * distance / length;
+        }
+        None
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        Some(self.bounds.clone())
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lambertian::Lambertian;
+    use crate::sdf;
+    use crate::sphere::Sphere;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn sphere_matches_the_analytic_one() {
+        let material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+            Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let center = Vec3::new(0.5, -1.0, 2.0);
+        let bounds = Aabb::new(
+            &center - &Vec3::new(1.5, 1.5, 1.5),
+            &center + &Vec3::new(1.5, 1.5, 1.5),
+        );
+        let shape = SdfShape::new(
+            sdf::translate(sdf::sphere(1.2), center.clone()),
+            bounds,
+            material.clone(),
+        );
+        let sphere = Sphere::new(center.clone(), 1.2, material);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..500 {
+            // Aimed near the sphere, from outside its bounds
+            let from = &center + &(5.0 * &Vec3::random_unit_vector(&mut rng));
+            let target = &center + &(rng.gen_range(0.0..1.5) * &Vec3::random_unit_vector(&mut rng));
+            let r = Ray::new(from.clone(), target - from, 0.0);
+            match (
+                sphere.hit(&r, 0.001, f64::INFINITY),
+                shape.hit(&r, 0.001, f64::INFINITY),
+            ) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    // Within EPSILON of the surface, further along grazing rays
+                    let cos = a.normal.dot(&r.direction().clone().normalize()).abs();
+                    let tolerance = 2.0 * EPSILON / cos.max(1e-2);
+                    assert!((&a.p - &b.p).length() < tolerance, "{:?} != {:?}", a.p, b.p);
+                    let normal = (&b.p - &center).normalize();
+                    let normal = if b.front_face { normal } else { -normal };
+                    assert!((&normal - &b.normal).length() < 1e-6);
+                    assert_eq!(a.front_face, b.front_face);
+                }
+                // Rays grazing the sphere may stop short of the surface
+                (Some(a), None) | (None, Some(a)) => {
+                    let distance = r.direction().cross(&(&center - r.origin())).length()
+                        / r.direction().length();
+                    assert!((distance - 1.2).abs() < 1e-2, "missed at {:?}", a.p);
+                }
+            }
+        }
+    }
+}
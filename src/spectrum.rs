@@ -0,0 +1,225 @@
+use crate::vec3::{Color, Vec3};
+use std::sync::OnceLock;
+
+// Wavelengths in nm that paths are traced at
+pub const LAMBDA_MIN: f64 = 380.0;
+pub const LAMBDA_MAX: f64 = 720.0;
+
+// Reflectances from which RGB colors are built, in 10 equal bins over
+// [LAMBDA_MIN, LAMBDA_MAX] (Smits 1999, "An RGB-to-Spectrum Conversion for
+// Reflectances")
+const WHITE: [f64; 10] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const CYAN: [f64; 10] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const MAGENTA: [f64; 10] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const YELLOW: [f64; 10] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const RED: [f64; 10] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const GREEN: [f64; 10] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const BLUE: [f64; 10] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+// Linearly interpolated between the centers of the bins
+fn table(values: &[f64; 10], lambda: f64) -> f64 {
+    let width = (LAMBDA_MAX - LAMBDA_MIN) / values.len() as f64;
+    let x = ((lambda - LAMBDA_MIN) / width - 0.5).clamp(0.0, (values.len() - 1) as f64);
+    let i = (x as usize).min(values.len() - 2);
+    let t = x - i as f64;
+    (1.0 - t) * values[i] + t * values[i + 1]
+}
+
+fn rgb_to_spectrum(rgb: &Color, lambda: f64) -> f64 {
+    let (r, g, b) = (rgb.x, rgb.y, rgb.z);
+    let at = |values: &[f64; 10]| table(values, lambda);
+    if r <= g && r <= b {
+        r * at(&WHITE)
+            + if g <= b {
+                (g - r) * at(&CYAN) + (b - g) * at(&BLUE)
+            } else {
+                (b - r) * at(&CYAN) + (g - b) * at(&GREEN)
+            }
+    } else if g <= r && g <= b {
+        g * at(&WHITE)
+            + if r <= b {
+                (r - g) * at(&MAGENTA) + (b - r) * at(&BLUE)
+            } else {
+                (b - g) * at(&MAGENTA) + (r - b) * at(&RED)
+            }
+    } else {
+        b * at(&WHITE)
+            + if r <= g {
+                (r - b) * at(&YELLOW) + (g - r) * at(&GREEN)
+            } else {
+                (g - b) * at(&YELLOW) + (r - g) * at(&RED)
+            }
+    }
+}
+
+// Piecewise Gaussian lobe, with different widths on either side of mu
+fn lobe(lambda: f64, mu: f64, sigma_below: f64, sigma_above: f64) -> f64 {
+    let sigma = if lambda < mu {
+        sigma_below
+    } else {
+        sigma_above
+    };
+    (-0.5 * ((lambda - mu) / sigma).powi(2)).exp()
+}
+
+// CIE 1931 color matching functions, as fitted by Wyman, Sloan and Shirley
+// 2013, "Simple Analytic Approximations to the CIE XYZ Color Matching Functions"
+fn color_matching(lambda: f64) -> Vec3 {
+    Vec3::new(
+        1.056 * lobe(lambda, 599.8, 37.9, 31.0) + 0.362 * lobe(lambda, 442.0, 16.0, 26.7)
+            - 0.065 * lobe(lambda, 501.1, 20.4, 26.2),
+        0.821 * lobe(lambda, 568.8, 46.9, 40.5) + 0.286 * lobe(lambda, 530.9, 16.3, 31.1),
+        1.217 * lobe(lambda, 437.0, 11.8, 36.0) + 0.681 * lobe(lambda, 459.0, 26.0, 13.8),
+    )
+}
+
+fn xyz_to_linear_srgb(xyz: &Vec3) -> Color {
+    Color::new(
+        3.2406 * xyz.x - 1.5372 * xyz.y - 0.4986 * xyz.z,
+        -0.9689 * xyz.x + 1.8758 * xyz.y + 0.0415 * xyz.z,
+        0.0557 * xyz.x - 0.2040 * xyz.y + 1.0570 * xyz.z,
+    )
+}
+
+// Integral of the luminance matching function over the traced range, and the
+// per channel scale that maps a constant spectrum of one to RGB white, as RGB
+// lights and backgrounds are taken to be relative to it
+struct Calibration {
+    y_integral: f64,
+    white_balance: Color,
+}
+
+fn calibration() -> &'static Calibration {
+    static CALIBRATION: OnceLock<Calibration> = OnceLock::new();
+    CALIBRATION.get_or_init(|| {
+        let steps = 4 * (LAMBDA_MAX - LAMBDA_MIN) as usize;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let integral = (0..steps).fold(Vec3::new(0.0, 0.0, 0.0), |sum, i| {
+            sum + step * &color_matching(LAMBDA_MIN + (i as f64 + 0.5) * step)
+        });
+        let white = xyz_to_linear_srgb(&(&integral / integral.y));
+        Calibration {
+            y_integral: integral.y,
+            white_balance: Color::new(1.0 / white.x, 1.0 / white.y, 1.0 / white.z),
+        }
+    })
+}
+
+// The wavelengths that a path carries, one per channel of Color: a hero
+// wavelength and two more spaced evenly from it over the traced range, which
+// share its path until a dispersive surface separates them (Wilkie et al.
+// 2014, "Hero Wavelength Spectral Sampling").
+pub struct Wavelengths {
+    lambda: [f64; 3],
+    pdf: [f64; 3],
+}
+
+impl Wavelengths {
+    // From a uniform random number u in [0, 1)
+    pub fn sample(u: f64) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let lambda = [0.0, 1.0, 2.0].map(|i| LAMBDA_MIN + (u + i / 3.0).fract() * range);
+        Wavelengths {
+            lambda,
+            pdf: [1.0 / range; 3],
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    pub fn lambda(&self) -> [f64; 3] {
+        self.lambda
+    }
+
+    // Drops all but the hero wavelength, once the path depends on it. Its
+    // density is split accordingly, so that it stands in for the others.
+    pub fn terminate_secondary(&mut self) {
+        if self.pdf[1] == 0.0 {
+            return;
+        }
+        self.pdf = [self.pdf[0] / 3.0, 0.0, 0.0];
+    }
+
+    // An RGB color as a spectrum, at these wavelengths
+    pub fn uplift(&self, rgb: &Color) -> Color {
+        let [l0, l1, l2] = self.lambda.map(|lambda| rgb_to_spectrum(rgb, lambda));
+        Color::new(l0, l1, l2)
+    }
+
+    // Linear sRGB estimate of a spectrum from its values at these wavelengths
+    pub fn to_rgb(&self, values: &Color) -> Color {
+        let calibration = calibration();
+        let xyz = (0..3)
+            .filter(|&i| self.pdf[i] > 0.0)
+            .fold(Vec3::new(0.0, 0.0, 0.0), |xyz, i| {
+                xyz + (values[i] / self.pdf[i]) * &color_matching(self.lambda[i])
+            });
+        let xyz = &xyz / (3.0 * calibration.y_integral);
+        &xyz_to_linear_srgb(&xyz) * &calibration.white_balance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Average of to_rgb over evenly spread hero wavelengths
+    fn mean_rgb(values: impl Fn(&Wavelengths) -> Color) -> Color {
+        let n = 1000;
+        (0..n).fold(Color::new(0.0, 0.0, 0.0), |sum, i| {
+            let wavelengths = Wavelengths::sample((i as f64 + 0.5) / n as f64);
+            sum + &wavelengths.to_rgb(&values(&wavelengths)) / n as f64
+        })
+    }
+
+    #[test]
+    fn constant_one_spectrum_is_white() {
+        let rgb = mean_rgb(|_| Color::new(1.0, 1.0, 1.0));
+        for i in 0..3 {
+            assert!((rgb[i] - 1.0).abs() < 1e-3, "{:?}", rgb);
+        }
+    }
+
+    #[test]
+    fn uplifted_colors_round_trip() {
+        for color in [
+            Color::new(1.0, 1.0, 1.0),
+            Color::new(0.5, 0.5, 0.5),
+            Color::new(0.8, 0.3, 0.1),
+            Color::new(0.1, 0.6, 0.9),
+        ]
+        .iter()
+        {
+            let rgb = mean_rgb(|wavelengths| wavelengths.uplift(color));
+            for i in 0..3 {
+                assert!((rgb[i] - color[i]).abs() < 0.05, "{:?} != {:?}", rgb, color);
+            }
+        }
+    }
+
+    #[test]
+    fn wavelengths_cover_the_range() {
+        let wavelengths = Wavelengths::sample(0.9);
+        let lambda = wavelengths.lambda();
+        assert_eq!(lambda[0], wavelengths.hero());
+        assert!(lambda
+            .iter()
+            .all(|&l| (LAMBDA_MIN..LAMBDA_MAX).contains(&l)));
+    }
+}
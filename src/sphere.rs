@@ -1,9 +1,20 @@
+use crate::aabb::Aabb;
 use crate::hittable::*;
-use crate::material::Material;
-use crate::vec3::Point3;
+use crate::material::{Material, MaterialIds};
+use crate::vec3::{Point3, Vec3};
 use crate::Ray;
+use std::f64::consts::PI;
 use std::sync::Arc;
 
+// p: a given point on the sphere of radius one, centered at the origin.
+// u: returned value [0,1] of angle around the Y axis from X=-1.
+// v: returned value [0,1] of angle from Y=-1 to Y=+1.
+pub fn get_sphere_uv(p: &Point3) -> (f64, f64) {
+    let theta = (-p.y).acos();
+    let phi = (-p.z).atan2(p.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
 pub struct Sphere {
     center: Point3,
     radius: f64,
@@ -48,12 +59,48 @@ impl Hittable for Sphere {
 
         let p = r.at(root);
         let outward_normal = (&p - &(self.center)) / self.radius;
-        Some(HitRecord::from_face_normal(
+        let mut rec = HitRecord::from_face_normal(
             r,
             p,
             &outward_normal,
             root,
+            get_sphere_uv(&outward_normal),
             self.mat_ptr.clone(),
-        ))
+        );
+        // Along the lines of latitude, for anisotropic materials
+        rec.tangent = Some(Vec3::new(-outward_normal.z, 0.0, outward_normal.x));
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let r = self.radius.abs();
+        let r = Vec3::new(r, r, r);
+        Some(Aabb::new(&self.center - &r, &self.center + &r))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+
+    fn sample_surface(&self) -> Option<HitRecord> {
+        let outward_normal = Vec3::random_unit_vector(&mut rand::thread_rng());
+        let (u, v) = get_sphere_uv(&outward_normal);
+        Some(HitRecord {
+            p: &self.center + self.radius * &outward_normal,
+            normal: outward_normal,
+            u,
+            v,
+            mat_ptr: self.mat_ptr.clone(),
+            t: 0.0,
+            front_face: true,
+            object_id: None,
+            material_id: None,
+            tangent: None,
+            collision_weight: None,
+        })
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius.powi(2)
     }
 }
@@ -0,0 +1,276 @@
+use crate::aabb::Aabb;
+use crate::henyey_greenstein::HenyeyGreenstein;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::microfacet::fresnel_dielectric;
+use crate::ray::{reflect, refract};
+use crate::spectrum::Wavelengths;
+use crate::vec3::Color;
+use crate::Ray;
+use rand::Rng;
+use std::sync::Arc;
+
+// Scattering properties of the inside, with extinction in 1 / scene units
+struct Medium {
+    albedo: Color,
+    sigma_t: Color,
+    g: f64,
+    index_of_refraction: f64,
+}
+
+impl Medium {
+    // Distance to the next scattering event, by the extinction of a channel
+    // picked at random, so that every channel is sampled well
+    fn sample_distance(&self) -> f64 {
+        let mut rng = rand::thread_rng();
+        let sigma_t = self.sigma_t[rng.gen_range(0..3)];
+        -(1.0 - rng.gen::<f64>()).ln() / sigma_t
+    }
+
+    // Density of sample_distance
+    fn distance_pdf(&self, distance: f64) -> f64 {
+        (0..3)
+            .map(|i| self.sigma_t[i] * (-self.sigma_t[i] * distance).exp())
+            .sum::<f64>()
+            / 3.0
+    }
+
+    // Chance that sample_distance goes further than the distance
+    fn escape_probability(&self, distance: f64) -> f64 {
+        (0..3)
+            .map(|i| (-self.sigma_t[i] * distance).exp())
+            .sum::<f64>()
+            / 3.0
+    }
+
+    // Albedo and extinction at the wavelengths of a path. The mean free
+    // path is uplifted as a color, relative to its longest channel.
+    fn at_wavelengths(&self, wavelengths: &Wavelengths) -> (Color, Color) {
+        let mean_free_path = Color::new(
+            1.0 / self.sigma_t.x,
+            1.0 / self.sigma_t.y,
+            1.0 / self.sigma_t.z,
+        );
+        let longest = mean_free_path.x.max(mean_free_path.y).max(mean_free_path.z);
+        let relative = wavelengths.uplift(&(&mean_free_path / longest));
+        let sigma_t = Color::new(
+            1.0 / (longest * relative.x.max(1e-3)),
+            1.0 / (longest * relative.y.max(1e-3)),
+            1.0 / (longest * relative.z.max(1e-3)),
+        );
+        (wavelengths.uplift(&self.albedo), sigma_t)
+    }
+}
+
+fn transmittance(sigma_t: &Color, distance: f64) -> Color {
+    Color::new(
+        (-sigma_t.x * distance).exp(),
+        (-sigma_t.y * distance).exp(),
+        (-sigma_t.z * distance).exp(),
+    )
+}
+
+// Length of the segment that a ray travelled to reach a hit
+fn segment_length(r_in: &Ray, rec: &HitRecord) -> f64 {
+    (&rec.p - r_in.origin()).length()
+}
+
+// Smooth dielectric surface of the object. Rays leaving it from inside are
+// weighted by the transmittance of the last segment of their walk over the
+// chance of having got that far.
+struct Interface {
+    medium: Arc<Medium>,
+}
+
+impl Interface {
+    fn scatter_with(&self, r_in: &Ray, rec: &HitRecord, sigma_t: &Color) -> Option<(Color, Ray)> {
+        let weight = if rec.front_face {
+            Color::new(1.0, 1.0, 1.0)
+        } else {
+            let distance = segment_length(r_in, rec);
+            &transmittance(sigma_t, distance) / self.medium.escape_probability(distance)
+        };
+
+        let index = self.medium.index_of_refraction;
+        let refraction_ratio = if rec.front_face { 1.0 / index } else { index };
+        let unit_direction = r_in.direction().clone().normalize();
+        let cos_theta = (-unit_direction.dot(&rec.normal)).min(1.0);
+        let direction = if rand::thread_rng().gen::<f64>()
+            < fresnel_dielectric(cos_theta, 1.0 / refraction_ratio)
+        {
+            reflect(&unit_direction, &rec.normal)
+        } else {
+            refract(&unit_direction, &rec.normal, refraction_ratio)
+        };
+        Some((weight, Ray::new(rec.p.clone(), direction, r_in.time())))
+    }
+}
+
+impl Material for Interface {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        self.scatter_with(r_in, rec, &self.medium.sigma_t)
+    }
+
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: &mut Wavelengths,
+    ) -> Option<(Color, Ray)> {
+        let (_, sigma_t) = self.medium.at_wavelengths(wavelengths);
+        self.scatter_with(r_in, rec, &sigma_t)
+    }
+}
+
+// Scattering event inside the object, weighted by the scattering coefficient
+// and transmittance of the segment that led to it over its density. Connecting
+// to it through the surface is impossible, so it counts as specular.
+struct Interior {
+    medium: Arc<Medium>,
+    phase: HenyeyGreenstein,
+}
+
+impl Interior {
+    fn scatter_with(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        albedo: &Color,
+        sigma_t: &Color,
+    ) -> Option<(Color, Ray)> {
+        let distance = segment_length(r_in, rec);
+        let sigma_s = albedo * sigma_t;
+        let weight =
+            &(&sigma_s * &transmittance(sigma_t, distance)) / self.medium.distance_pdf(distance);
+        // The phase function is white, so only its direction is of use
+        let (_, scattered) = self.phase.scatter(r_in, rec)?;
+        Some((weight, scattered))
+    }
+}
+
+impl Material for Interior {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        self.scatter_with(r_in, rec, &self.medium.albedo, &self.medium.sigma_t)
+    }
+
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: &mut Wavelengths,
+    ) -> Option<(Color, Ray)> {
+        let (albedo, sigma_t) = self.medium.at_wavelengths(wavelengths);
+        self.scatter_with(r_in, rec, &albedo, &sigma_t)
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+}
+
+// Translucent solid such as skin, wax, marble or milk: a closed boundary with
+// a smooth dielectric surface, inside which light takes a random walk through
+// a scattering medium. The mean free path is the average distance between
+// scattering events per channel, in scene units, and the albedo the chance of
+// scattering rather than being absorbed at each. Walks count towards the
+// maximum depth, so mean free paths much shorter than the object and albedos
+// close to one lose light. Nothing else may be inside the boundary.
+pub struct Subsurface {
+    boundary: Box<dyn Hittable + Sync + Send + 'static>,
+    interface: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    interior: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    medium: Arc<Medium>,
+}
+
+impl Subsurface {
+    pub fn new(
+        boundary: impl Hittable + Sync + Send + 'static,
+        albedo: Color,
+        mean_free_path: Color,
+        index_of_refraction: f64,
+    ) -> Self {
+        let medium = Medium {
+            albedo,
+            sigma_t: Color::new(
+                1.0 / mean_free_path.x,
+                1.0 / mean_free_path.y,
+                1.0 / mean_free_path.z,
+            ),
+            g: 0.0,
+            index_of_refraction,
+        };
+        Self::with_medium(Box::new(boundary), medium)
+    }
+
+    fn with_medium(boundary: Box<dyn Hittable + Sync + Send + 'static>, medium: Medium) -> Self {
+        let medium = Arc::new(medium);
+        Subsurface {
+            boundary,
+            interface: Arc::new(Box::new(Interface {
+                medium: medium.clone(),
+            })),
+            interior: Arc::new(Box::new(Interior {
+                medium: medium.clone(),
+                phase: HenyeyGreenstein::new(Color::new(1.0, 1.0, 1.0), medium.g),
+            })),
+            medium,
+        }
+    }
+
+    // Henyey-Greenstein g of scattering inside, forward for g > 0
+    pub fn with_anisotropy(self, g: f64) -> Self {
+        let medium = Medium {
+            albedo: self.medium.albedo.clone(),
+            sigma_t: self.medium.sigma_t.clone(),
+            g,
+            index_of_refraction: self.medium.index_of_refraction,
+        };
+        Self::with_medium(self.boundary, medium)
+    }
+}
+
+impl Hittable for Subsurface {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Rays that meet the boundary from behind start inside it
+        let mut rec = self.boundary.hit(r, t_min, f64::INFINITY)?;
+        if !rec.front_face {
+            let t = t_min.max(0.0) + self.medium.sample_distance() / r.direction().length();
+            if t < rec.t {
+                if t >= t_max {
+                    return None;
+                }
+                return Some(HitRecord {
+                    p: r.at(t),
+                    normal: rec.normal,
+                    mat_ptr: self.interior.clone(),
+                    t,
+                    u: rec.u,
+                    v: rec.v,
+                    front_face: true,
+                    object_id: None,
+                    material_id: None,
+                    tangent: None,
+                    collision_weight: None,
+                });
+            }
+        }
+        if rec.t >= t_max {
+            return None;
+        }
+        rec.mat_ptr = self.interface.clone();
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.boundary.bounding_box(time0, time1)
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.interface);
+        ids.id(&self.interior);
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + std::mem::size_of::<Medium>() + self.boundary.memory_size()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::vec3::{Color, Point3};
+
+// Color varying over a surface, looked up by the u and v of a hit
+pub trait Texture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+// A plain color is the same everywhere
+impl Texture for Color {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        self.clone()
+    }
+}
+
+// width * height pixels stored row by row from the top, so v = 1 is the
+// first row. Lookups are bilinearly filtered and clamped at the edges.
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl ImageTexture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height, "ImageTexture size mismatch.");
+        assert!(width > 0 && height > 0, "ImageTexture is empty.");
+        ImageTexture {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // Evaluates f at the center of every pixel, f receives u and v in [0, 1]
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(f64, f64) -> Color) -> Self {
+        let pixels = (0..height)
+            .flat_map(|j| (0..width).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                f(
+                    (i as f64 + 0.5) / width as f64,
+                    1.0 - (j as f64 + 0.5) / height as f64,
+                )
+            })
+            .collect();
+        ImageTexture::new(width, height, pixels)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Column i of row j, counted from the top
+    pub fn pixel(&self, i: usize, j: usize) -> &Color {
+        &self.pixels[i + self.width * j]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        // Continuous pixel coordinates, with pixel centers at integers
+        let x = (u.clamp(0.0, 1.0) * self.width as f64 - 0.5).max(0.0);
+        let y = ((1.0 - v.clamp(0.0, 1.0)) * self.height as f64 - 0.5).max(0.0);
+        let (i, j) = (x as usize, y as usize);
+        let (i, j) = (i.min(self.width - 1), j.min(self.height - 1));
+        let (i1, j1) = ((i + 1).min(self.width - 1), (j + 1).min(self.height - 1));
+        let (fx, fy) = (x - i as f64, y - j as f64);
+
+        let top = (1.0 - fx) * self.pixel(i, j) + fx * self.pixel(i1, j);
+        let bottom = (1.0 - fx) * self.pixel(i, j1) + fx * self.pixel(i1, j1);
+        (1.0 - fy) * top + fy * bottom
+    }
+}
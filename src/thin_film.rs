@@ -0,0 +1,273 @@
+use crate::hittable::HitRecord;
+use crate::material::Material;
+use crate::ray::{reflect, refract};
+use crate::spectrum::Wavelengths;
+use crate::texture::Texture;
+use crate::Color;
+use crate::Ray;
+use rand::Rng;
+use std::ops::{Add, Div, Mul, Sub};
+
+// Wavelengths in nm that stand for the RGB channels, as for Conductor
+const RGB_WAVELENGTHS: [f64; 3] = [650.0, 550.0, 450.0];
+
+#[derive(Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    fn real(re: f64) -> Self {
+        Complex::new(re, 0.0)
+    }
+
+    // e^(i phi)
+    fn phase(phi: f64) -> Self {
+        Complex::new(phi.cos(), phi.sin())
+    }
+
+    fn norm(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    // Principal square root
+    fn sqrt(self) -> Self {
+        let r = self.norm().sqrt();
+        let re = (0.5 * (r + self.re)).max(0.0).sqrt();
+        let im = (0.5 * (r - self.re)).max(0.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, other: Complex) -> Complex {
+        let d = other.norm();
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / d,
+            (self.im * other.re - self.re * other.im) / d,
+        )
+    }
+}
+
+// Amplitude reflection coefficients (s, p) of an interface from index n_i to
+// n_j, with the cosines of the angles on either side
+fn fresnel_amplitudes(
+    n_i: Complex,
+    cos_i: Complex,
+    n_j: Complex,
+    cos_j: Complex,
+) -> (Complex, Complex) {
+    let s = (n_i * cos_i - n_j * cos_j) / (n_i * cos_i + n_j * cos_j);
+    let p = (n_j * cos_i - n_i * cos_j) / (n_j * cos_i + n_i * cos_j);
+    (s, p)
+}
+
+// Reflectance of a film of index n_film and the given thickness, between
+// the incident medium n_1 and a substrate n_3 that may absorb, summing the
+// reflections inside the film coherently (Airy)
+fn film_reflectance(
+    cos_theta: f64,
+    n_1: f64,
+    n_film: f64,
+    n_3: Complex,
+    thickness: f64,
+    wavelength: f64,
+) -> f64 {
+    let sin2 = (1.0 - cos_theta * cos_theta).max(0.0);
+    let sin2_film = sin2 * (n_1 / n_film).powi(2);
+    if sin2_film >= 1.0 {
+        // Totally reflected before reaching into the film
+        return 1.0;
+    }
+    let cos_film = (1.0 - sin2_film).sqrt();
+    let cos_3 = (Complex::real(1.0) - Complex::real(sin2 * n_1 * n_1) / (n_3 * n_3)).sqrt();
+
+    let (n_1, n_film, cos_1, cos_f) = (
+        Complex::real(n_1),
+        Complex::real(n_film),
+        Complex::real(cos_theta),
+        Complex::real(cos_film),
+    );
+    let (r12_s, r12_p) = fresnel_amplitudes(n_1, cos_1, n_film, cos_f);
+    let (r23_s, r23_p) = fresnel_amplitudes(n_film, cos_f, n_3, cos_3);
+
+    // Phase difference of a round trip through the film
+    let delta =
+        Complex::phase(4.0 * std::f64::consts::PI * n_film.re * thickness * cos_film / wavelength);
+    let airy = |r12: Complex, r23: Complex| {
+        ((r12 + r23 * delta) / (Complex::real(1.0) + r12 * r23 * delta)).norm()
+    };
+    (0.5 * (airy(r12_s, r23_s) + airy(r12_p, r23_p))).clamp(0.0, 1.0)
+}
+
+// A quantity given per RGB channel, interpolated linearly between the
+// wavelengths of the channels
+fn at_wavelength(c: &Color, lambda: f64) -> f64 {
+    let [red, green, blue] = RGB_WAVELENGTHS;
+    if lambda > green {
+        let t = ((lambda - green) / (red - green)).min(1.0);
+        (1.0 - t) * c.y + t * c.x
+    } else {
+        let t = ((green - lambda) / (green - blue)).min(1.0);
+        (1.0 - t) * c.y + t * c.z
+    }
+}
+
+enum Substrate {
+    Dielectric(f64),
+    // Complex index eta + i k per RGB channel, as for Conductor
+    Conductor(Color, Color),
+}
+
+// A smooth surface under a thin transparent film, like a soap bubble (a film
+// with air on both sides), oil on water or oxide on metal. Light reflected off
+// the top and bottom of the film interferes, coloring reflections by film
+// thickness and viewing angle. Thickness is in nanometers, a textured one is
+// read from the red channel. RGB rendering evaluates the film at one
+// wavelength per channel, --spectral at the wavelengths of each path.
+pub struct ThinFilm {
+    substrate: Substrate,
+    film_index: f64,
+    thickness: Box<dyn Texture + Sync + Send>,
+}
+
+impl ThinFilm {
+    pub fn dielectric(index_of_refraction: f64, film_index: f64, thickness: f64) -> Self {
+        ThinFilm {
+            substrate: Substrate::Dielectric(index_of_refraction),
+            film_index,
+            thickness: Box::new(Color::new(thickness, thickness, thickness)),
+        }
+    }
+
+    pub fn conductor(eta: Color, k: Color, film_index: f64, thickness: f64) -> Self {
+        ThinFilm {
+            substrate: Substrate::Conductor(eta, k),
+            film_index,
+            thickness: Box::new(Color::new(thickness, thickness, thickness)),
+        }
+    }
+
+    pub fn with_thickness_texture(
+        mut self,
+        thickness: impl Texture + Sync + Send + 'static,
+    ) -> Self {
+        self.thickness = Box::new(thickness);
+        self
+    }
+
+    fn scatter_at(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: [f64; 3],
+    ) -> Option<(Color, Ray)> {
+        let unit_direction = r_in.direction().clone().normalize();
+        let cos_theta = (-unit_direction.dot(&rec.normal)).min(1.0);
+        let thickness = self.thickness.value(rec.u, rec.v, &rec.p).x.max(0.0);
+        let reflected = Ray::new(
+            rec.p.clone(),
+            reflect(&unit_direction, &rec.normal),
+            r_in.time(),
+        );
+
+        match &self.substrate {
+            Substrate::Conductor(eta, k) => {
+                let reflectance = wavelengths.map(|lambda| {
+                    let n_3 = Complex::new(at_wavelength(eta, lambda), at_wavelength(k, lambda));
+                    film_reflectance(cos_theta, 1.0, self.film_index, n_3, thickness, lambda)
+                });
+                let reflectance = Color::new(reflectance[0], reflectance[1], reflectance[2]);
+                Some((reflectance, reflected))
+            }
+            Substrate::Dielectric(index) => {
+                // The film keeps to the outside of the substrate
+                let (n_1, n_3) = if rec.front_face {
+                    (1.0, *index)
+                } else {
+                    (*index, 1.0)
+                };
+                let reflectance = wavelengths.map(|lambda| {
+                    film_reflectance(
+                        cos_theta,
+                        n_1,
+                        self.film_index,
+                        Complex::real(n_3),
+                        thickness,
+                        lambda,
+                    )
+                });
+                let reflectance = Color::new(reflectance[0], reflectance[1], reflectance[2]);
+
+                // Pick a side by the mean reflectance, weighting by the rest.
+                // Snell's law through parallel interfaces ignores the film.
+                let ratio = n_1 / n_3;
+                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                let p_reflect = if ratio * sin_theta > 1.0 {
+                    1.0
+                } else {
+                    (reflectance.x + reflectance.y + reflectance.z) / 3.0
+                };
+                if rand::thread_rng().gen::<f64>() < p_reflect {
+                    Some((reflectance / p_reflect, reflected))
+                } else {
+                    let transmittance = Color::new(
+                        1.0 - reflectance.x,
+                        1.0 - reflectance.y,
+                        1.0 - reflectance.z,
+                    );
+                    let refracted = refract(&unit_direction, &rec.normal, ratio);
+                    Some((
+                        transmittance / (1.0 - p_reflect),
+                        Ray::new(rec.p.clone(), refracted, r_in.time()),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl Material for ThinFilm {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        self.scatter_at(r_in, rec, RGB_WAVELENGTHS)
+    }
+
+    fn scatter_spectral(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        wavelengths: &mut Wavelengths,
+    ) -> Option<(Color, Ray)> {
+        self.scatter_at(r_in, rec, wavelengths.lambda())
+    }
+}
@@ -0,0 +1,241 @@
+// Two-level acceleration structure. Every shared object gets its own
+// bottom-level BVH, built once, and the top level is a BVH over instances
+// that each hold a transform and a reference to one of them.
+
+use crate::aabb::Aabb;
+use crate::bvh::BvhNode;
+use crate::hittable::*;
+use crate::hittable_list::HittableList;
+use crate::instance::Instance;
+use crate::material::MaterialIds;
+use crate::transform::Transform;
+use crate::Ray;
+use std::fmt;
+use std::sync::Arc;
+
+// Bottom level: the BVH of one shared object, such as a mesh
+pub struct Blas {
+    bvh: BvhNode,
+    // Bytes of the primitives alone, what every flattened copy would cost
+    primitives_size: usize,
+}
+
+impl Blas {
+    pub fn new(list: HittableList, time0: f64, time1: f64) -> Self {
+        let primitives_size = list.memory_size() - std::mem::size_of::<HittableList>();
+        Blas {
+            bvh: BvhNode::new(list, time0, time1),
+            primitives_size,
+        }
+    }
+}
+
+impl Hittable for Blas {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh.hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.bvh.bounding_box(time0, time1)
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        self.bvh.number_materials(ids)
+    }
+
+    fn traversal_cost(&self, r: &Ray, t_min: f64, t_max: f64) -> usize {
+        self.bvh.traversal_cost(r, t_min, t_max)
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() - std::mem::size_of::<BvhNode>() + self.bvh.memory_size()
+    }
+}
+
+// Top level: a BVH over the instances, each bottom level is stored once
+pub struct Tlas {
+    top: BvhNode,
+    meshes: Vec<Arc<Blas>>,
+    // Index into meshes of every instance
+    instance_meshes: Vec<usize>,
+}
+
+impl Tlas {
+    pub fn new(instances: Vec<(Arc<Blas>, Transform)>, time0: f64, time1: f64) -> Self {
+        let mut meshes: Vec<Arc<Blas>> = Vec::new();
+        let mut instance_meshes = Vec::with_capacity(instances.len());
+        let list = instances
+            .into_iter()
+            .fold(HittableList::new(), |list, (mesh, transform)| {
+                let index = match meshes.iter().position(|m| Arc::ptr_eq(m, &mesh)) {
+                    Some(index) => index,
+                    None => {
+                        meshes.push(mesh.clone());
+                        meshes.len() - 1
+                    }
+                };
+                instance_meshes.push(index);
+                list.push(Instance::new(mesh, transform))
+            });
+        Tlas {
+            top: BvhNode::new(list, time0, time1),
+            meshes,
+            instance_meshes,
+        }
+    }
+
+    pub fn memory_report(&self) -> MemoryReport {
+        MemoryReport {
+            instances: self.instance_meshes.len(),
+            meshes: self.meshes.len(),
+            two_level: self.memory_size()
+                + self
+                    .meshes
+                    .iter()
+                    .map(|mesh| mesh.memory_size())
+                    .sum::<usize>(),
+            flattened: std::mem::size_of::<HittableList>()
+                + self
+                    .instance_meshes
+                    .iter()
+                    .map(|&index| self.meshes[index].primitives_size)
+                    .sum::<usize>(),
+        }
+    }
+}
+
+impl Hittable for Tlas {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.top.hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.top.bounding_box(time0, time1)
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        self.top.number_materials(ids)
+    }
+
+    fn traversal_cost(&self, r: &Ray, t_min: f64, t_max: f64) -> usize {
+        self.top.traversal_cost(r, t_min, t_max)
+    }
+
+    // The shared bottom levels are not included, see memory_report
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.top.memory_size()
+            + self.meshes.capacity() * std::mem::size_of::<Arc<Blas>>()
+            + self.instance_meshes.capacity() * std::mem::size_of::<usize>()
+    }
+}
+
+// Bytes used by a two-level structure, against copying every instance's
+// primitives into one flat list
+pub struct MemoryReport {
+    pub instances: usize,
+    pub meshes: usize,
+    pub two_level: usize,
+    pub flattened: usize,
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} instances of {} meshes: {:.1} KiB in two levels, {:.1} KiB flattened",
+            self.instances,
+            self.meshes,
+            self.two_level as f64 / 1024.0,
+            self.flattened as f64 / 1024.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lambertian::Lambertian;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::vec3::{Color, Point3, Vec3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn mesh(rng: &mut StdRng) -> HittableList {
+        let material: Arc<Box<dyn Material + Sync + Send + 'static>> =
+            Arc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        (0..20).fold(HittableList::new(), |list, _| {
+            let center = Point3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            list.push(Sphere::new(
+                center,
+                rng.gen_range(0.05..0.3),
+                material.clone(),
+            ))
+        })
+    }
+
+    #[test]
+    fn same_hits_as_a_list_of_instances() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let meshes = [
+            Arc::new(Blas::new(mesh(&mut rng), 0.0, 0.0)),
+            Arc::new(Blas::new(mesh(&mut rng), 0.0, 0.0)),
+        ];
+        let instances: Vec<(Arc<Blas>, Transform)> = (0..30)
+            .map(|i| {
+                let transform = Transform::identity()
+                    .scale(Vec3::new(
+                        rng.gen_range(0.5..2.0),
+                        rng.gen_range(0.5..2.0),
+                        rng.gen_range(0.5..2.0),
+                    ))
+                    .rotate(
+                        Vec3::random_unit_vector(&mut rng),
+                        rng.gen_range(0.0..360.0),
+                    )
+                    .translate(Vec3::new(
+                        rng.gen_range(-8.0..8.0),
+                        rng.gen_range(-8.0..8.0),
+                        rng.gen_range(-8.0..8.0),
+                    ));
+                (meshes[i % 2].clone(), transform)
+            })
+            .collect();
+        let brute_force = instances
+            .iter()
+            .fold(HittableList::new(), |list, (mesh, transform)| {
+                list.push(Instance::new(mesh.clone(), transform.clone()))
+            });
+        let tlas = Tlas::new(instances, 0.0, 0.0);
+
+        for _ in 0..2000 {
+            let origin = Point3::new(
+                rng.gen_range(-12.0..12.0),
+                rng.gen_range(-12.0..12.0),
+                rng.gen_range(-12.0..12.0),
+            );
+            let r = Ray::new(origin, Vec3::random_unit_vector(&mut rng), 0.0);
+            match (
+                brute_force.hit(&r, 0.001, f64::INFINITY),
+                tlas.hit(&r, 0.001, f64::INFINITY),
+            ) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    assert!((a.t - b.t).abs() < 1e-9, "{} != {}", a.t, b.t);
+                    assert!((&a.normal - &b.normal).length() < 1e-9);
+                }
+                (a, b) => panic!("list hit {}, tlas hit {}", a.is_some(), b.is_some()),
+            }
+        }
+
+        let report = tlas.memory_report();
+        assert_eq!(report.instances, 30);
+        assert_eq!(report.meshes, 2);
+        assert!(report.two_level < report.flattened);
+    }
+}
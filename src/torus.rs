@@ -0,0 +1,96 @@
+use crate::aabb::Aabb;
+use crate::hittable::*;
+use crate::material::{Material, MaterialIds};
+use crate::roots;
+use crate::vec3::{Point3, Vec3};
+use crate::Ray;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+// Torus around the Y axis: a tube of minor_radius swept along a circle of major_radius.
+// u: angle around the Y axis, v: angle around the tube.
+pub struct Torus {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+    mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point3,
+        major_radius: f64,
+        minor_radius: f64,
+        mat_ptr: Arc<Box<dyn Material + Sync + Send + 'static>>,
+    ) -> Torus {
+        Torus {
+            center,
+            major_radius,
+            minor_radius,
+            mat_ptr,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let length = r.direction().length();
+        let d = r.direction() / length;
+        let o = r.origin() - &self.center;
+
+        // The quartic is solved from the point closest to the center, with a unit
+        // direction, which keeps its coefficients well conditioned.
+        let shift = -o.dot(&d);
+        let o = &o + shift * &d;
+        let bound = self.major_radius + self.minor_radius;
+        if o.norm() > bound * bound {
+            return None;
+        }
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (p.x^2 + p.z^2)
+        let r2 = self.major_radius.powi(2);
+        let e = o.norm() + r2 - self.minor_radius.powi(2);
+        let f = o.dot(&d);
+        let axial = d.x * d.x + d.z * d.z;
+        let cross = o.x * d.x + o.z * d.z;
+        let radial = o.x * o.x + o.z * o.z;
+        let t = roots::quartic(
+            1.0,
+            4.0 * f,
+            4.0 * f * f + 2.0 * e - 4.0 * r2 * axial,
+            4.0 * e * f - 8.0 * r2 * cross,
+            e * e - 4.0 * r2 * radial,
+        )
+        .into_iter()
+        .map(|t| (t + shift) / length)
+        .find(|&t| t_min <= t && t <= t_max)?;
+
+        let p = r.at(t);
+        let local = &p - &self.center;
+        // The normal points away from the closest point of the center circle
+        let rho = (local.x * local.x + local.z * local.z).sqrt();
+        let ring = (self.major_radius / rho) * Vec3::new(local.x, 0.0, local.z);
+        let outward_normal = (&local - &ring) / self.minor_radius;
+
+        let phi = (-local.z).atan2(local.x) + PI;
+        let theta = local.y.atan2(rho - self.major_radius) + PI;
+        Some(HitRecord::from_face_normal(
+            r,
+            p,
+            &outward_normal,
+            t,
+            (phi / (2.0 * PI), theta / (2.0 * PI)),
+            self.mat_ptr.clone(),
+        ))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Aabb> {
+        let extent = self.major_radius + self.minor_radius;
+        let e = Vec3::new(extent, self.minor_radius, extent);
+        Some(Aabb::new(&self.center - &e, &self.center + &e))
+    }
+
+    fn number_materials(&self, ids: &mut MaterialIds) {
+        ids.id(&self.mat_ptr);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::util::degrees_to_radians;
+use crate::vec3::{Point3, Vec3};
+use std::ops::Mul;
+
+// Row major 4x4 matrix acting on column vectors
+#[derive(Clone, Debug)]
+pub struct Matrix4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn identity() -> Self {
+        Matrix4::scaling(&Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn translation(v: &Vec3) -> Self {
+        Matrix4::identity().with_translation(v)
+    }
+
+    pub fn scaling(s: &Vec3) -> Self {
+        Matrix4::from_rows([[s.x, 0.0, 0.0], [0.0, s.y, 0.0], [0.0, 0.0, s.z]])
+    }
+
+    // Counterclockwise rotation around axis, looking against it
+    pub fn rotation(axis: &Vec3, degrees: f64) -> Self {
+        let a = axis.clone().normalize();
+        let (s, c) = degrees_to_radians(degrees).sin_cos();
+        let t = 1.0 - c;
+        Matrix4::from_rows([
+            [
+                t * a.x * a.x + c,
+                t * a.x * a.y - s * a.z,
+                t * a.x * a.z + s * a.y,
+            ],
+            [
+                t * a.x * a.y + s * a.z,
+                t * a.y * a.y + c,
+                t * a.y * a.z - s * a.x,
+            ],
+            [
+                t * a.x * a.z - s * a.y,
+                t * a.y * a.z + s * a.x,
+                t * a.z * a.z + c,
+            ],
+        ])
+    }
+
+    // Linear part only, without translation
+    fn from_rows(rows: [[f64; 3]; 3]) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in rows.iter().enumerate() {
+            m[i][..3].copy_from_slice(row);
+        }
+        m[3][3] = 1.0;
+        Matrix4 { m }
+    }
+
+    fn with_translation(mut self, v: &Vec3) -> Self {
+        self.m[0][3] = v.x;
+        self.m[1][3] = v.y;
+        self.m[2][3] = v.z;
+        self
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Matrix4 { m }
+    }
+
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let m = &self.m;
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3];
+        let z = m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3];
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        if w == 1.0 {
+            Point3::new(x, y, z)
+        } else {
+            Point3::new(x / w, y / w, z / w)
+        }
+    }
+
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+impl Mul for &Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            }
+        }
+        Matrix4 { m }
+    }
+}
+
+// Affine transform from object to world space, kept together with its inverse.
+// Steps are chained in the order they apply:
+// Transform::identity().scale(..).rotate(..).translate(..)
+#[derive(Clone, Debug)]
+pub struct Transform {
+    matrix: Matrix4,
+    inverse: Matrix4,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            matrix: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+        }
+    }
+
+    pub fn translate(self, v: Vec3) -> Self {
+        let inverse = Matrix4::translation(&-v.clone());
+        self.then(Matrix4::translation(&v), inverse)
+    }
+
+    pub fn scale(self, s: Vec3) -> Self {
+        let inverse = Matrix4::scaling(&Vec3::new(1.0 / s.x, 1.0 / s.y, 1.0 / s.z));
+        self.then(Matrix4::scaling(&s), inverse)
+    }
+
+    pub fn rotate(self, axis: Vec3, degrees: f64) -> Self {
+        let inverse = Matrix4::rotation(&axis, -degrees);
+        self.then(Matrix4::rotation(&axis, degrees), inverse)
+    }
+
+    pub fn rotate_y(self, degrees: f64) -> Self {
+        self.rotate(Vec3::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    fn then(self, step: Matrix4, step_inverse: Matrix4) -> Self {
+        Transform {
+            matrix: &step * &self.matrix,
+            inverse: &self.inverse * &step_inverse,
+        }
+    }
+
+    pub fn point(&self, p: &Point3) -> Point3 {
+        self.matrix.transform_point(p)
+    }
+
+    pub fn vector(&self, v: &Vec3) -> Vec3 {
+        self.matrix.transform_vector(v)
+    }
+
+    // Normals stay perpendicular to surfaces under the inverse transpose
+    pub fn normal(&self, n: &Vec3) -> Vec3 {
+        self.inverse.transpose().transform_vector(n).normalize()
+    }
+
+    pub fn inverse_point(&self, p: &Point3) -> Point3 {
+        self.inverse.transform_point(p)
+    }
+
+    pub fn inverse_vector(&self, v: &Vec3) -> Vec3 {
+        self.inverse.transform_vector(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Vec3, b: &Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    fn transform() -> Transform {
+        Transform::identity()
+            .scale(Vec3::new(2.0, 0.5, 3.0))
+            .rotate(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            .rotate_y(-75.0)
+            .translate(Vec3::new(1.0, -2.0, 5.0))
+    }
+
+    #[test]
+    fn inverse_round_trip() {
+        let t = transform();
+        let p = Point3::new(0.3, -1.2, 4.0);
+        let v = Vec3::new(-2.0, 0.7, 1.5);
+        assert_close(&t.inverse_point(&t.point(&p)), &p);
+        assert_close(&t.point(&t.inverse_point(&p)), &p);
+        assert_close(&t.inverse_vector(&t.vector(&v)), &v);
+    }
+
+    #[test]
+    fn translation_moves_points_only() {
+        let t = Transform::identity().translate(Vec3::new(1.0, 2.0, 3.0));
+        let v = Vec3::new(1.0, 1.0, 1.0);
+        assert_close(&t.point(&v), &Vec3::new(2.0, 3.0, 4.0));
+        assert_close(&t.vector(&v), &v);
+    }
+
+    #[test]
+    fn normals_stay_perpendicular() {
+        let t = transform();
+        let (u, v) = (Vec3::new(1.0, 0.2, 0.0), Vec3::new(0.0, -0.4, 1.0));
+        let n = t.normal(&u.cross(&v));
+        assert!((n.length() - 1.0).abs() < 1e-9);
+        assert!(n.dot(&t.vector(&u)).abs() < 1e-9);
+        assert!(n.dot(&t.vector(&v)).abs() < 1e-9);
+        // Same side as the transformed surface's own normal
+        assert!(n.dot(&t.vector(&u).cross(&t.vector(&v))) > 0.0);
+    }
+}
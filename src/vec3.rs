@@ -1,4 +1,4 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub, SubAssign};
 
 pub type Point3 = Vec3;
 pub type Color = Vec3;
@@ -12,7 +12,7 @@ pub struct Vec3 {
 
 impl Vec3 {
     pub fn new(x: f64, y: f64, z: f64) -> Vec3 {
-        Vec3 { x: x, y: y, z: z }
+        Vec3 { x, y, z }
     }
     pub fn length(&self) -> f64 {
         self.norm().sqrt()
@@ -73,7 +73,7 @@ impl Vec3 {
 
     pub fn random_in_unit_disk(rng: &mut impl rand::Rng) -> Self {
         loop {
-            let p = Vec3::new(rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0), 0.0);
+            let p = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0);
             if p.norm() < 1.0 {
                 return p;
             }
@@ -86,6 +86,19 @@ impl Vec3 {
     }
 }
 
+impl Index<usize> for Vec3 {
+    type Output = f64;
+
+    fn index(&self, axis: usize) -> &f64 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 index out of range: {}", axis),
+        }
+    }
+}
+
 impl AddAssign<&Vec3> for Vec3 {
     fn add_assign(&mut self, other: &Self) {
         self.x += other.x;